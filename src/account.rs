@@ -10,7 +10,17 @@ pub struct Account {
     pub name: String,
     pub r#type: String,
     pub transactions: Vec<String>,
-    pub parent: String
+    pub parent: String,
+    #[serde(default)]
+    pub closed: bool,
+    // closed accounts are hidden when choosing an account but keep their history
+    #[serde(default)]
+    pub cash: bool,
+    // cash accounts are the ones the cash flow statement follows
+    #[serde(default)]
+    pub category: String
+    // cash flow category of "operating", "investing" or "financing".
+    // left empty the account takes the category of its parent
 
 }
 
@@ -31,7 +41,10 @@ impl Account {
             name: "".to_string(),
             r#type: "".to_string(),
             transactions: Vec::new(),
-            parent: "".to_string()
+            parent: "".to_string(),
+            closed: false,
+            cash: false,
+            category: "".to_string()
         }
     }
 
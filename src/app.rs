@@ -1,13 +1,50 @@
-use std::path::{PathBuf};
+use std::path::{Path, PathBuf};
 use crate::company::Company;
+use crate::account::Account;
+use crate::transaction::Transaction;
+use crate::budget::Budget;
+use crate::recurrence::Recurrence;
+use crate::history::History;
+use crate::reconcile::Reconciliation;
+use crate::query::{self, Query};
+use crate::report::ExpenseReport;
+use crate::service::{ServiceEvent, ServiceJob, ServiceRegistry};
+use crate::events::Publisher;
+use crate::filter::{Comparison, Condition};
+use crate::protocol::{ReportParams, Request};
+use crate::keymap::{Action, Key, Keymap};
+use crate::theme::Theme;
+use crate::state::State;
+use crate::features::{self, Feature, FEATURES};
+use crate::manual::{self, Topic};
+use crate::tutorial::Tutorial;
+use crate::sandbox::{self, Sandbox};
+use crate::events;
+use crate::mouse::Screen;
+use crate::command;
+use crate::config::Config;
+use crate::file_io;
+use std::error::Error;
 use std::io;
+use std::time::SystemTime;
+use chrono::prelude::*;
 
 pub enum InputMode {
     Normal,
     Editing,
 }
 
-#[derive(PartialEq)]
+// The series the dashboard charts, worked out once and kept until the
+// ledger or the query changes
+#[derive(Default)]
+pub struct Dashboard {
+    pub net_worth: Vec<(NaiveDate, f64)>,
+    pub balances: Vec<(String, Vec<(NaiveDate, f64)>)>,
+    // the balance history of each account charted so far, by account_id
+    pub months: Vec<(NaiveDate, f64, f64)>,
+}
+
+#[derive(PartialEq, Clone, Copy)]
 pub enum Focus {
     Nothing,
     Charts,
@@ -16,18 +53,71 @@ pub enum Focus {
     NewTransaction,
     Register,
     DeleteTransaction,
+    EditTransaction,
+    Budget,
+    Upcoming,
+    Reconcile,
+    Filter,
+    Groups,
+    CashFlow,
+    Dashboard,
+    Sandbox,
+    WhatsNew,
+    Help,
 }
 
 pub struct App {
     pub input: String,
     pub input_mode: InputMode,
     pub messages: Vec<String>,
-    pub progress: f64,
     pub db_path: PathBuf,
+    pub db_modified: Option<SystemTime>,
+    // when the database file was last read or written here
     pub company: Company,
     pub focus: Focus,
-    pub report_gen_done: bool,
     pub report_path: PathBuf,
+    pub report_period: String,
+    pub reconciliation: Option<Reconciliation>,
+    pub pending_delete: Option<String>,
+    pub pending_edit: Option<String>,
+    // an edit of a reconciled transaction, waiting to be entered again
+    pub query: Query,
+    pub history: History,
+    pub chart_collapsed: Vec<String>,
+    pub chart_selected: usize,
+    pub chart_action: Option<String>,
+    pub services: ServiceRegistry,
+    pub service_job: Option<ServiceJob>,
+    pub publisher: Option<Publisher>,
+    pub config: Config,
+    // the settings in effect, for formatting and the net worth accounts
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub state: State,
+    pub notices: Vec<&'static Feature>,
+    // the features shown by the what's new panel
+    pub notice_selected: usize,
+    pub help_topic: Option<&'static Topic>,
+    // the topic being read, or None for the list of topics
+    pub help_search: String,
+    pub help_selected: usize,
+    pub help_return: Focus,
+    // the view to go back to when the manual is closed
+    pub tutorial: Option<Tutorial>,
+    pub sandbox: Option<Sandbox>,
+    // the real ledger, set aside while changes are tried out on a copy
+    pub quit_warned: bool,
+    pub screen: Screen,
+    // where the last frame drew what the mouse can click
+    pub viewer_scroll: u16,
+    pub viewer_scrolled: Focus,
+    // the view viewer_scroll belongs to, so another view starts at the top
+    pub messages_scroll: usize,
+    pub register_selected: Option<String>,
+    // the transaction_id picked in the register
+    pub recalled: Option<usize>,
+    // the command from the history in the input, while Up and Down walk it
+    pub dashboard: Option<Dashboard>,
 }
 
 impl Default for App {
@@ -36,12 +126,43 @@ impl Default for App {
             input: String::new(),
             input_mode: InputMode::Normal,
             messages: Vec::new(),
-            progress: 0.0,
             db_path: PathBuf::new(),
+            db_modified: None,
             company: Company::default(),
             focus: Focus::Nothing,
-            report_gen_done: false,
             report_path: PathBuf::new(),
+            report_period: "month".to_string(),
+            reconciliation: None,
+            pending_delete: None,
+            pending_edit: None,
+            query: Query::new(),
+            history: History::new(),
+            chart_collapsed: Vec::new(),
+            chart_selected: 0,
+            chart_action: None,
+            services: ServiceRegistry::default(),
+            service_job: None,
+            publisher: None,
+            config: Config::default(),
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            state: State::default(),
+            notices: Vec::new(),
+            notice_selected: 0,
+            help_topic: None,
+            help_search: String::new(),
+            help_selected: 0,
+            help_return: Focus::Nothing,
+            tutorial: None,
+            sandbox: None,
+            quit_warned: false,
+            screen: Screen::default(),
+            viewer_scroll: 0,
+            viewer_scrolled: Focus::Nothing,
+            messages_scroll: 0,
+            register_selected: None,
+            recalled: None,
+            dashboard: None,
         }
     }
 }
@@ -57,22 +178,1264 @@ impl App {
 
     }
 
+    pub fn get_viewer_scroll(&mut self) -> u16 {
+        if self.viewer_scrolled != self.focus {
+            self.viewer_scroll = 0;
+            self.viewer_scrolled = self.focus;
+        }
+
+        self.viewer_scroll
+    }
+
+    pub fn stop_editing(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.chart_action = None;
+        self.recalled = None;
+    }
+
+    pub fn start_command(&mut self) {
+        self.input = ":".to_string();
+        self.input_mode = InputMode::Editing;
+        self.recalled = None;
+    }
+
+    pub fn cancel_confirmation(&mut self) {
+        // answers no to whatever was asked
+        if let Some(id) = self.pending_delete.take() {
+            self.messages.push(format!("Kept transaction_id {}", id));
+        }
+        if self.pending_edit.take().is_some() {
+            self.messages.push("Left the transaction as it was".to_string());
+        }
+        self.quit_warned = false;
+    }
+
+    pub fn get_view(action: Action) -> Option<Focus> {
+        // the view an action opens, if it opens one
+        match action {
+            Action::Charts => Some(Focus::Charts),
+            Action::ExpenseReport => Some(Focus::ExpenseReport),
+            Action::BalanceSheet => Some(Focus::BalanceSheet),
+            Action::NewTransaction => Some(Focus::NewTransaction),
+            Action::Register => Some(Focus::Register),
+            Action::DeleteTransaction => Some(Focus::DeleteTransaction),
+            Action::EditTransaction => Some(Focus::EditTransaction),
+            Action::Budget => Some(Focus::Budget),
+            Action::CashFlow => Some(Focus::CashFlow),
+            Action::Dashboard => Some(Focus::Dashboard),
+            Action::Upcoming => Some(Focus::Upcoming),
+            Action::Reconcile => Some(Focus::Reconcile),
+            Action::Groups => Some(Focus::Groups),
+            Action::Filter => Some(Focus::Filter),
+            Action::Sandbox => Some(Focus::Sandbox),
+            _ => None,
+        }
+    }
+
+    pub fn is_available(&self, action: Action) -> bool {
+        // whether the action does anything in the focused view
+        match action {
+            Action::Up | Action::Down => self.focus == Focus::Charts
+                || self.focus == Focus::Reconcile
+                || self.focus == Focus::WhatsNew
+                || self.focus == Focus::Help,
+            Action::Select => self.focus == Focus::Charts
+                || self.focus == Focus::WhatsNew
+                || self.focus == Focus::Help,
+            Action::Left | Action::Right | Action::AddAccount | Action::RenameAccount => {
+                self.focus == Focus::Charts
+            },
+            Action::Dismiss => self.focus == Focus::WhatsNew || self.focus == Focus::Help,
+            Action::CyclePeriod => self.focus == Focus::Budget
+                || self.focus == Focus::CashFlow
+                || self.focus == Focus::ExpenseReport,
+            Action::Approve => self.focus == Focus::Upcoming,
+            Action::ToggleCleared | Action::FinishReconciliation => self.focus == Focus::Reconcile,
+            Action::CancelService => self.service_job.is_some(),
+            _ => true,
+        }
+    }
+
+    pub fn press_key(&mut self, key: Key) -> Option<Action> {
+        // the keymap is set aside while it asks what the focused view offers
+        let mut keymap = std::mem::take(&mut self.keymap);
+        let action = keymap.press(key, |action| self.is_available(action));
+        self.keymap = keymap;
+
+        action
+    }
+
+    pub fn cycle_report_period(&mut self) {
+        self.report_period = match &self.report_period[..] {
+            "month" => "quarter".to_string(),
+            "quarter" => "year".to_string(),
+            _ => "month".to_string(),
+        };
+    }
+
     pub fn on_tick(&mut self) {
-        self.progress += 0.01;
-        if self.progress > 1.0 {
-            self.progress = 0.0;
+        self.check_tutorial();
+
+        // messages from the report service request, if one is running
+        let events = match &self.service_job {
+            Some(job) => job.poll(),
+            None => return,
+        };
+
+        for event in events {
+            match event {
+                ServiceEvent::Retrying(attempt, reason) => {
+                    self.messages.push(format!("Report service attempt {}: {}", attempt, reason));
+                },
+                ServiceEvent::Finished(result) => {
+                    match result {
+                        Ok(path) => {
+                            self.messages.push(format!("The report service wrote {}", path.display()));
+                            self.report_path = path;
+                        },
+                        Err(err) => self.messages.push(format!("The report service failed: {}", err)),
+                    }
+                    self.service_job = None;
+                },
+            }
+        }
+    }
+
+    pub fn start_service_report(&mut self, format: &str) {
+        if self.service_job.is_some() {
+            self.messages.push(format!(
+                "The report service is already working. Press {} to cancel",
+                self.keymap.get_keys(Action::CancelService)
+            ));
+            return;
+        }
+
+        let service = match self.services.get_for_action("expense_report") {
+            Some(service) => service.clone(),
+            None => {
+                self.messages.push("No service is registered for expense_report".to_string());
+                return;
+            },
+        };
+
+        // the report covers the current period and any accounts in the query
+        let (start, end) = self.company.get_current_period(&self.report_period);
+        let accounts = self.query.filter.conditions
+            .iter()
+            .filter_map(|condition| match condition {
+                Condition::Account(Comparison::Equal | Comparison::Contains, _, ids) => Some(ids.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let params = ReportParams {
+            period: self.report_period.clone(),
+            start: Some(start),
+            end: Some(end),
+            accounts,
+            format: format.to_string(),
+        };
+
+        // the ledger as the app has it, which in the sandbox is not on disk
+        let request = serde_json::to_string(&self.company)
+            .map_err(|err| err.into())
+            .and_then(|database| Request::expense_report(&database, params));
+
+        match request {
+            Ok(request) => {
+                let directory = self.db_path.parent().unwrap_or_else(|| Path::new("."));
+                self.messages.push(format!("Asking the {} service at {}", service.name, service.endpoint));
+                self.service_job = Some(ServiceJob::start(&service, request, directory));
+            },
+            Err(err) => self.messages.push(format!("Could not send the ledger: {}", err)),
+        }
+    }
+
+    pub fn cancel_service_report(&mut self) {
+        // the worker notices on its next wake up and reports back as cancelled
+        if let Some(job) = &self.service_job {
+            job.cancel();
+            self.messages.push("Cancelling the report service request".to_string());
         }
     }
 
     pub fn load_company(mut self) -> io::Result<Self> {
         self.company = self.company.load(self.db_path.as_path())?;
+        self.db_modified = file_io::get_modified(&self.db_path);
+        self.dashboard = None;
         Ok(self)
     }
 
+    pub fn reload_company(&mut self) -> Result<(), Box<dyn Error>> {
+        // a database saved by another program, such as money serve, since it
+        // was last read here replaces the ledger in memory so its changes are
+        // not written over. the snapshots to undo are of the old ledger and go
+        if self.sandbox.is_some() {
+            return Ok(());
+        }
+        let modified = file_io::get_modified(&self.db_path);
+        if modified.is_some() && modified != self.db_modified {
+            self.company = Company::default().load(self.db_path.as_path())?;
+            self.db_modified = modified;
+            self.history = History::new();
+            self.forget_summaries();
+            self.messages.push("Reloaded the database, which another program has changed".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn save_company(&mut self) -> Result<(), Box<dyn Error>> {
+        // nothing is written while the sandbox is open, nor over a database
+        // another program saved after it was read here
+        if self.sandbox.is_some() {
+            return Ok(());
+        }
+        let modified = file_io::get_modified(&self.db_path);
+        if modified.is_some() && modified != self.db_modified {
+            return Err("another program has changed the database since it was read".into());
+        }
+        let path = self.db_path.to_str().ok_or("database path is not valid unicode")?;
+        self.company.write_to(path)?;
+        self.db_modified = file_io::get_modified(&self.db_path);
+        Ok(())
+    }
+
+    pub fn check_recurrences(&mut self) {
+        // let the user know about scheduled transactions waiting for approval
+        let today = Local::today().naive_local();
+        let due = self.company.get_due_recurrences(today).len();
+
+        if due > 0 {
+            self.messages.push(format!(
+                "{} scheduled transactions are due. Press {} to review them",
+                due, self.keymap.get_keys(Action::Upcoming)
+            ));
+        }
+    }
+
+    pub fn approve_recurrences(&mut self) {
+        // posts every due scheduled transaction
+        let today = Local::today().naive_local();
+
+        self.commit("post scheduled transactions", |company| {
+            let posted = company.post_due_recurrences(today)?;
+            if posted.is_empty() {
+                return Err("none are due".into());
+            }
+            Ok(format!("Posted {} scheduled transactions", posted.len()))
+        });
+    }
+
+    pub fn commit<F>(&mut self, label: &str, change: F) -> bool
+    where F: FnOnce(&mut Company) -> Result<String, Box<dyn Error>>
+    {
+        // applies a change to the company, keeping a snapshot to undo it
+        // and saving the database. a failed change leaves the company as it was
+        if let Err(err) = self.reload_company() {
+            self.messages.push(format!("Could not reload the database: {}", err));
+            return false;
+        }
+        self.history.record(label, &self.company);
+
+        match change(&mut self.company) {
+            Ok(message) => {
+                self.forget_summaries();
+                self.messages.push(message);
+                // nothing is announced that did not reach the database
+                if let Err(err) = self.save_company() {
+                    self.messages.push(format!("Could not save the database: {}", err));
+                    return true;
+                }
+                self.history.confirm();
+                if let (Some(publisher), Some((_, before))) = (self.publisher.as_mut(), self.history.undo.last()) {
+                    publisher.publish_changes(label, before, &self.company);
+                }
+                true
+            },
+            Err(err) => {
+                self.history.rollback(&mut self.company);
+                self.messages.push(format!("Could not {}: {}", label, err));
+                false
+            },
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if let Err(err) = self.reload_company() {
+            self.messages.push(format!("Could not reload the database: {}", err));
+            return;
+        }
+        match self.history.undo(&mut self.company) {
+            Some(label) => {
+                self.forget_summaries();
+                self.messages.push(format!("Undid {}", label));
+                if let Err(err) = self.save_company() {
+                    self.messages.push(format!("Could not save the database: {}", err));
+                    return;
+                }
+                if let (Some(publisher), Some((_, before))) = (self.publisher.as_mut(), self.history.redo.last()) {
+                    publisher.publish_changes(&format!("undo {}", label), before, &self.company);
+                }
+            },
+            None => self.messages.push("Nothing to undo".to_string()),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Err(err) = self.reload_company() {
+            self.messages.push(format!("Could not reload the database: {}", err));
+            return;
+        }
+        match self.history.redo(&mut self.company) {
+            Some(label) => {
+                self.forget_summaries();
+                self.messages.push(format!("Redid {}", label));
+                if let Err(err) = self.save_company() {
+                    self.messages.push(format!("Could not save the database: {}", err));
+                    return;
+                }
+                if let (Some(publisher), Some((_, before))) = (self.publisher.as_mut(), self.history.undo.last()) {
+                    publisher.publish_changes(&format!("redo {}", label), before, &self.company);
+                }
+            },
+            None => self.messages.push("Nothing to redo".to_string()),
+        }
+    }
+
     pub fn database<T>(mut self, path: T) -> Self
     where T: Into<PathBuf>
     {
         self.db_path = path.into();
         self
     }
-}
\ No newline at end of file
+
+    pub fn services(mut self, services: ServiceRegistry) -> Self {
+        self.services = services;
+        self
+    }
+
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn forget_summaries(&mut self) {
+        // the dashboard and the sandbox comparison are worked out again
+        // the next time they are drawn
+        self.dashboard = None;
+        if let Some(sandbox) = self.sandbox.as_mut() {
+            sandbox.summary = None;
+        }
+    }
+
+    pub fn update_sandbox_summary(&mut self) {
+        if let Some(sandbox) = self.sandbox.as_mut() {
+            if sandbox.summary.is_none() {
+                sandbox.summary = Some(sandbox.summarize(&self.company));
+            }
+        }
+    }
+
+    pub fn update_dashboard(&mut self, account_id: Option<&str>) {
+        // works out the series the first time the dashboard is drawn after
+        // a change, and the balance history of an account the first time it is picked
+        if self.dashboard.is_none() {
+            let company = self.company.filtered(&self.query.filter);
+            self.dashboard = Some(Dashboard {
+                net_worth: company.get_net_worth_history(&self.config.assets_account, &self.config.liabilities_account),
+                balances: Vec::new(),
+                months: company.get_monthly_income_expense(&self.config.income_account, &self.config.expenses_account),
+            });
+        }
+
+        if let (Some(dashboard), Some(id)) = (self.dashboard.as_mut(), account_id) {
+            if !dashboard.balances.iter().any(|(charted, _)| charted == id) {
+                let company = self.company.filtered(&self.query.filter);
+                dashboard.balances.push((id.to_string(), company.get_balance_history(id)));
+            }
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        self.config.format_date(date)
+    }
+
+    pub fn format_amount(&self, amount: f64) -> String {
+        self.config.format_amount(amount)
+    }
+
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn state(mut self, state: State) -> Self {
+        // opens what's new when the user has not seen everything yet
+        self.notices = features::get_new_since(state.last_seen_version.as_deref());
+        if !self.notices.is_empty() {
+            self.focus = Focus::WhatsNew;
+        }
+        self.state = state;
+        self
+    }
+
+    pub fn tutorial(mut self, tutorial: Tutorial) -> Self {
+        self.db_path = tutorial.get_database();
+        self.messages.push(format!("Tutorial: working on a copy of the sample ledger in {}", tutorial.scratch.display()));
+        self.tutorial = Some(tutorial);
+        self
+    }
+
+    pub fn check_tutorial(&mut self) {
+        // taken out while it looks at the rest of the app
+        if let Some(mut tutorial) = self.tutorial.take() {
+            if let Some(message) = tutorial.check(self) {
+                self.messages.push(message);
+            }
+            self.tutorial = Some(tutorial);
+        }
+    }
+
+    pub fn toggle_whats_new(&mut self) {
+        if self.focus == Focus::WhatsNew {
+            return self.dismiss_notices();
+        }
+
+        // with nothing new, every feature is listed
+        if self.notices.is_empty() {
+            self.notices = FEATURES.iter().collect();
+        }
+        self.focus = Focus::WhatsNew;
+    }
+
+    pub fn dismiss_notices(&mut self) {
+        // the notices stay away until a newer version adds features
+        self.focus = Focus::Nothing;
+        if self.state.last_seen_version.as_deref() == Some(features::CURRENT_VERSION) {
+            return;
+        }
+
+        self.state.last_seen_version = Some(features::CURRENT_VERSION.to_string());
+        if let Err(err) = self.state.save() {
+            self.messages.push(format!("Could not remember what is new: {}", err));
+        }
+    }
+
+    pub fn notice_select_next(&mut self) {
+        if self.notice_selected + 1 < self.notices.len() {
+            self.notice_selected += 1;
+        }
+    }
+
+    pub fn notice_select_previous(&mut self) {
+        self.notice_selected = self.notice_selected.saturating_sub(1);
+    }
+
+    pub fn open_notice(&mut self) {
+        // opens the feature with its tutorial in the messages
+        let feature = match self.notices.get(self.notice_selected) {
+            Some(feature) => *feature,
+            None => return,
+        };
+
+        self.dismiss_notices();
+        self.messages.push(format!("Trying {}", feature.name));
+        for (step, line) in feature.tutorial.iter().enumerate() {
+            self.messages.push(format!("{}. {}", step + 1, line));
+        }
+        if let Some(focus) = feature.action.and_then(App::get_view) {
+            self.focus = focus;
+        }
+    }
+
+    pub fn get_help_topic(focus: Focus) -> &'static str {
+        // the manual topic about a view
+        match focus {
+            Focus::Charts => "accounts",
+            Focus::ExpenseReport => "expenses",
+            Focus::BalanceSheet => "balance",
+            Focus::NewTransaction | Focus::DeleteTransaction | Focus::EditTransaction => "transactions",
+            Focus::Register => "register",
+            Focus::Budget => "budgets",
+            Focus::Upcoming => "scheduled",
+            Focus::Reconcile => "reconcile",
+            Focus::Filter => "query",
+            Focus::Groups => "groups",
+            Focus::CashFlow => "cashflow",
+            Focus::Dashboard => "dashboard",
+            Focus::Sandbox => "sandbox",
+            Focus::WhatsNew => "whats-new",
+            Focus::Nothing | Focus::Help => "overview",
+        }
+    }
+
+    pub fn toggle_help(&mut self) {
+        // opens the manual at the topic about the open view
+        if self.focus == Focus::Help {
+            self.focus = self.help_return;
+            return;
+        }
+
+        self.help_return = self.focus;
+        self.help_topic = manual::get_topic(App::get_help_topic(self.focus));
+        self.help_search.clear();
+        self.focus = Focus::Help;
+    }
+
+    pub fn close_help(&mut self) {
+        // a topic goes back to the list, and the list closes the manual
+        if self.help_topic.take().is_none() {
+            self.focus = self.help_return;
+        }
+    }
+
+    pub fn get_help_matches(&self) -> Vec<&'static Topic> {
+        manual::search(&self.help_search, &self.keymap)
+    }
+
+    pub fn search_help(&mut self, input: &str) {
+        self.help_search = input.trim().to_string();
+        self.help_topic = None;
+        self.help_selected = 0;
+
+        // a single match is opened straight away
+        let matches = self.get_help_matches();
+        match matches.len() {
+            0 => self.messages.push(format!("Nothing in the manual matches {}", self.help_search)),
+            1 => self.help_topic = Some(matches[0]),
+            _ => {},
+        }
+    }
+
+    pub fn help_select_next(&mut self) {
+        if self.help_selected + 1 < self.get_help_matches().len() {
+            self.help_selected += 1;
+        }
+    }
+
+    pub fn help_select_previous(&mut self) {
+        self.help_selected = self.help_selected.saturating_sub(1);
+    }
+
+    pub fn open_help_topic(&mut self) {
+        self.help_topic = self.get_help_matches().get(self.help_selected).copied();
+    }
+
+    pub fn publisher(mut self, publisher: Publisher) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
+    pub fn submit_input(&mut self) {
+        // what the input line means depends on the focused view
+        let reads_commands = self.reads_commands();
+        let input: String = self.input.drain(..).collect();
+        self.recalled = None;
+        self.quit_warned = false;
+
+        if reads_commands {
+            command::run(self, &input);
+            return;
+        }
+
+        match self.focus {
+            Focus::Reconcile => self.start_reconciliation(&input),
+            Focus::DeleteTransaction => self.delete_transaction(&input),
+            Focus::EditTransaction => self.edit_transaction(&input),
+            Focus::Filter => self.set_query(&input),
+            Focus::Register => self.set_register(&input),
+            Focus::Charts => self.submit_chart_action(&input),
+            Focus::ExpenseReport => self.export_expense_report(&input),
+            Focus::Help => self.search_help(&input),
+            Focus::NewTransaction => self.add_transaction(&input),
+            Focus::Sandbox => self.submit_sandbox(&input),
+            Focus::Budget => self.change_budget(&input),
+            Focus::Upcoming => self.change_recurrence(&input),
+            _ if !input.trim().is_empty() => {
+                self.messages.push(format!("This view takes no input, enter :{} to run it as a command", input.trim()));
+            },
+            _ => {},
+        }
+    }
+
+    pub fn reads_commands(&self) -> bool {
+        // only a colon starts a command, so a stray word is not run
+        self.input.starts_with(':')
+    }
+
+    pub fn export_expense_report(&mut self, input: &str) {
+        // reads "<csv|html|md> [path]", or "service [csv|html|md]" to ask
+        // the external report service instead
+        let mut parts = input.split_whitespace();
+        let format = parts.next().unwrap_or("").to_lowercase();
+
+        if format == "service" {
+            let format = parts.next().unwrap_or("html").to_lowercase();
+            self.start_service_report(&format);
+            return;
+        }
+
+        let (start, end) = self.company.get_current_period(&self.report_period);
+        let company = self.company.filtered(&self.query.filter);
+        let path = parts.next().map(PathBuf::from);
+        let directory = self.db_path.parent().unwrap_or_else(|| Path::new("."));
+
+        let exported = ExpenseReport::generate(&company, start, end)
+            .and_then(|report| report.export(&format, path.as_deref(), directory));
+
+        match exported {
+            Ok(path) => {
+                self.messages.push(format!("Exported the expense report to {}", path.display()));
+                self.report_path = path;
+            },
+            Err(err) => self.messages.push(format!("Could not export the report: {}", err)),
+        }
+    }
+
+    pub fn set_query(&mut self, input: &str) {
+        // the query filters the reports and picks what the register lists
+        self.register_selected = None;
+        match Query::parse(&self.company, input) {
+            Ok(query) => {
+                if query.is_empty() {
+                    self.messages.push("Cleared the query".to_string());
+                }
+                else {
+                    self.messages.push(format!("Querying {}", query.describe()));
+                }
+                self.query = query;
+                self.dashboard = None;
+            },
+            Err(err) => self.messages.push(format!("Could not read the query: {}", err)),
+        }
+    }
+
+    pub fn set_register(&mut self, input: &str) {
+        self.set_query(&App::get_register_terms(input));
+    }
+
+    pub fn get_register_terms(input: &str) -> String {
+        // a bare account name is short for account:<name>
+        let input = input.trim();
+        let is_term = input.contains([':', '=', '~', '<', '>', '#']);
+
+        if input.is_empty() || is_term {
+            input.to_string()
+        }
+        else {
+            format!("account:\"{}\"", input)
+        }
+    }
+
+    pub fn start_reconciliation(&mut self, input: &str) {
+        match Reconciliation::parse(&self.company, input) {
+            Ok(reconciliation) => {
+                self.messages.push(format!("Reconciling account_id {}", reconciliation.account));
+                self.reconciliation = Some(reconciliation);
+            },
+            Err(err) => self.messages.push(format!("Could not start reconciling: {}", err)),
+        }
+    }
+
+    pub fn finish_reconciliation(&mut self) {
+        let reconciliation = match self.reconciliation.clone() {
+            Some(reconciliation) => reconciliation,
+            None => return,
+        };
+
+        let done = self.commit("reconcile", |company| {
+            let count = reconciliation.finish(company)?;
+            Ok(format!("Reconciled {} transactions", count))
+        });
+
+        if done {
+            self.reconciliation = None;
+        }
+    }
+
+    pub fn add_transaction(&mut self, input: &str) {
+        // reads "[YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>]",
+        // with accounts by path, name or id. a memo of several words may be quoted
+        let mut words = query::split_words(input);
+        let payee = words
+            .iter()
+            .position(|word| word.starts_with("payee:"))
+            .map(|index| words.remove(index)["payee:".len()..].to_string());
+        let date = match words.first().map(|word| NaiveDate::parse_from_str(word, "%Y-%m-%d")) {
+            Some(Ok(date)) => {
+                words.remove(0);
+                Some(date)
+            },
+            _ => None,
+        };
+        if words.len() < 3 {
+            self.messages.push("Enter: [YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>]".to_string());
+            return;
+        }
+
+        let memo = words[3..].join(" ");
+        self.commit("add a transaction", |company| {
+            let debit = company.get_open_account_id(&words[0])?;
+            let credit = company.get_open_account_id(&words[1])?;
+            if debit == credit {
+                return Err("the debit and credit are the same account".into());
+            }
+            if words[2].parse::<f64>().map_or(true, |amount| amount <= 0.0) {
+                return Err(format!("{} is not a positive amount", words[2]).into());
+            }
+
+            let mut transaction = Transaction::new();
+            transaction
+                .set_debit(&debit)
+                .set_credit(&credit)
+                .set_amount(&words[2])
+                .set_memo(&memo);
+            if let Some(payee) = &payee {
+                transaction.set_payee(payee);
+            }
+            if let Some(date) = date {
+                transaction.date = date;
+            }
+
+            let id = company.post_transaction(transaction);
+            Ok(format!("Added transaction_id {}", id))
+        });
+    }
+
+    pub fn change_budget(&mut self, input: &str) {
+        // reads "set <account> <amount> [month|quarter|year]" or
+        // "delete <account>", with the account by path, name or id
+        let words = query::split_words(input);
+        let words = words.iter().map(|word| &word[..]).collect::<Vec<&str>>();
+
+        match words[..] {
+            ["set", account, amount] | ["set", account, amount, _] => {
+                let period = words.get(3).copied().unwrap_or("month");
+                self.commit("set a budget", |company| {
+                    let account = company.get_open_account_id(account)?;
+                    if amount.parse::<f64>().map_or(true, |amount| amount < 0.0) {
+                        return Err(format!("{} is not an amount", amount).into());
+                    }
+                    if !matches!(period, "month" | "quarter" | "year") {
+                        return Err(format!("{} is not month, quarter or year", period).into());
+                    }
+
+                    // setting it again keeps the id of the budget it replaces
+                    let mut budget = Budget::new();
+                    match company.get_budget_by_account(&account) {
+                        Some(old) => budget.id = old.id.clone(),
+                        None => {
+                            budget.set_id_in_company(company);
+                        },
+                    }
+                    budget
+                        .set_account(&account)
+                        .set_amount(amount)
+                        .set_period(period);
+                    company.insert_budget(budget);
+
+                    Ok(format!("Set the budget of {} to {} a {}", company.get_account_path(&account), amount, period))
+                });
+            },
+            ["delete", account] => {
+                self.commit("delete a budget", |company| {
+                    let id = company
+                        .find_account_by_path(account)
+                        .and_then(|account| company.get_budget_by_account(&account.id))
+                        .map(|budget| budget.id.clone())
+                        .ok_or_else(|| format!("{} has no budget", account))?;
+                    company.delete_budget(&id);
+
+                    Ok(format!("Deleted the budget of {}", account))
+                });
+            },
+            _ => self.messages.push("Enter set <account> <amount> [month|quarter|year] or delete <account>".to_string()),
+        }
+    }
+
+    pub fn change_recurrence(&mut self, input: &str) {
+        // reads "add <daily|weekly|monthly|yearly> <YYYY-MM-DD> <debit> <credit> <amount> [memo]"
+        // followed by any of until:<YYYY-MM-DD> and last-business-day,
+        // or "delete <id>"
+        let mut words = query::split_words(input);
+        let end = words
+            .iter()
+            .position(|word| word.starts_with("until:"))
+            .map(|index| words.remove(index)["until:".len()..].to_string());
+        let last_business_day = words
+            .iter()
+            .position(|word| word == "last-business-day")
+            .map(|index| words.remove(index))
+            .is_some();
+
+        match words.first().map(|word| &word[..]) {
+            Some("add") if words.len() >= 6 => {
+                self.commit("schedule a transaction", |company| {
+                    let frequency = &words[1];
+                    if !matches!(&frequency[..], "daily" | "weekly" | "monthly" | "yearly") {
+                        return Err(format!("{} is not daily, weekly, monthly or yearly", frequency).into());
+                    }
+                    let start = NaiveDate::parse_from_str(&words[2], "%Y-%m-%d")
+                        .map_err(|_| format!("{} is not a date", words[2]))?;
+                    let end = match &end {
+                        Some(end) => Some(NaiveDate::parse_from_str(end, "%Y-%m-%d").map_err(|_| format!("{} is not a date", end))?),
+                        None => None,
+                    };
+                    let debit = company.get_open_account_id(&words[3])?;
+                    let credit = company.get_open_account_id(&words[4])?;
+                    if words[5].parse::<f64>().map_or(true, |amount| amount <= 0.0) {
+                        return Err(format!("{} is not a positive amount", words[5]).into());
+                    }
+
+                    let mut recurrence = Recurrence::new();
+                    recurrence
+                        .set_id_in_company(company)
+                        .set_frequency(frequency)
+                        .set_start(start)
+                        .set_end(end)
+                        .set_last_business_day(last_business_day)
+                        .set_template(&debit, &credit, &words[5], &words[6..].join(" "));
+                    let id = recurrence.id.clone();
+                    let first = recurrence.get_next_date();
+                    company.insert_recurrence(recurrence);
+
+                    match first {
+                        Some(first) => Ok(format!("Scheduled recurrence {}, first due {}", id, first)),
+                        None => Err("it ends before it starts".into()),
+                    }
+                });
+            },
+            Some("delete") if words.len() == 2 => {
+                let id = words[1].clone();
+                self.commit("delete a scheduled transaction", |company| {
+                    if company.get_recurrence_by_id(&id).is_none() {
+                        return Err(format!("there is no recurrence {}", id).into());
+                    }
+                    company.delete_recurrence(&id);
+                    Ok(format!("Deleted recurrence {}", id))
+                });
+            },
+            _ => self.messages.push(
+                "Enter add <frequency> <YYYY-MM-DD> <debit> <credit> <amount> [memo] [until:<YYYY-MM-DD>] [last-business-day], or delete <id>".to_string()
+            ),
+        }
+    }
+
+    pub fn submit_sandbox(&mut self, input: &str) {
+        // reads start, commit, discard or import <path>
+        let input = input.trim();
+        let (command, rest) = input.split_once(' ').unwrap_or((input, ""));
+
+        match (command, self.sandbox.is_some()) {
+            ("start", false) => self.start_sandbox(),
+            ("start", true) => self.messages.push("The sandbox is already open".to_string()),
+            ("commit", true) => self.commit_sandbox(),
+            ("discard", true) => self.discard_sandbox(),
+            ("import", true) => {
+                let path = PathBuf::from(rest.trim());
+                self.commit("import transactions", |company| {
+                    let (posted, duplicates, skipped) = sandbox::import_transactions(company, &path)?;
+                    Ok(format!(
+                        "Imported {} transactions from {}, skipped {} already in the ledger and {} whose accounts are not",
+                        posted, path.display(), duplicates, skipped
+                    ))
+                });
+            },
+            (_, false) => self.messages.push("Enter start to open the sandbox".to_string()),
+            _ => self.messages.push("Enter commit, discard or import <path>".to_string()),
+        }
+    }
+
+    pub fn start_sandbox(&mut self) {
+        // the sandbox gets a history of its own, so undo cannot reach
+        // past the moment it was opened
+        self.sandbox = Some(Sandbox {
+            original: self.company.clone(),
+            history: std::mem::take(&mut self.history),
+            publisher: self.publisher.take(),
+            summary: None,
+        });
+        self.messages.push("Opened the sandbox. Nothing is saved until it is committed".to_string());
+    }
+
+    pub fn commit_sandbox(&mut self) {
+        // the changes become one change of the real ledger, undone in one step
+        let sandbox = match self.sandbox.take() {
+            Some(sandbox) => sandbox,
+            None => return,
+        };
+        self.history = sandbox.history;
+        self.publisher = sandbox.publisher;
+        self.quit_warned = false;
+
+        if events::diff(&sandbox.original, &self.company).is_empty() {
+            self.messages.push("Closed the sandbox, it had no changes".to_string());
+            return;
+        }
+
+        self.history.record("commit the sandbox", &sandbox.original);
+        if let Err(err) = self.save_company() {
+            self.messages.push(format!("Could not save the database: {}", err));
+            return;
+        }
+        self.history.confirm();
+        self.messages.push("Committed the sandbox to the database".to_string());
+        if let Some(publisher) = self.publisher.as_mut() {
+            publisher.publish_changes("commit the sandbox", &sandbox.original, &self.company);
+        }
+    }
+
+    pub fn discard_sandbox(&mut self) {
+        let sandbox = match self.sandbox.take() {
+            Some(sandbox) => sandbox,
+            None => return,
+        };
+        self.company = sandbox.original;
+        self.dashboard = None;
+        self.history = sandbox.history;
+        self.publisher = sandbox.publisher;
+        self.quit_warned = false;
+        self.messages.push("Discarded the sandbox, the ledger is as it was".to_string());
+    }
+
+    pub fn request_quit(&mut self) -> bool {
+        // quitting with changes in the sandbox warns once before they are lost
+        let changed = self.sandbox
+            .as_ref()
+            .is_some_and(|sandbox| !events::diff(&sandbox.original, &self.company).is_empty());
+        if changed && !self.quit_warned {
+            self.messages.push(format!(
+                "The sandbox has changes that are not committed. Press {} again to quit and lose them",
+                self.keymap.get_keys(Action::Quit)
+            ));
+            self.quit_warned = true;
+            return false;
+        }
+
+        true
+    }
+
+    pub fn delete_transaction(&mut self, input: &str) {
+        // reconciled transactions ask for the same id to be entered twice
+        self.confirm_delete(input, false);
+    }
+
+    pub fn click_delete(&mut self, id: &str) {
+        // a click is easier to make by mistake than typing an id, so every
+        // transaction asks the question a reconciled one asks of the keyboard
+        self.confirm_delete(id, true);
+    }
+
+    fn confirm_delete(&mut self, input: &str, always_ask: bool) {
+        let id = input.trim().to_string();
+        let confirmed = self.pending_delete.as_ref() == Some(&id);
+        self.pending_delete = None;
+
+        let reconciled = self.company
+            .get_transaction_by_id(&id)
+            .is_some_and(|transaction| transaction.is_reconciled());
+        if (reconciled || always_ask) && !confirmed {
+            if reconciled {
+                self.messages.push(format!(
+                    "Warning: transaction_id {} has been reconciled with the bank. Enter the id again to delete it anyway", id
+                ));
+            }
+            else {
+                self.messages.push(format!("Delete transaction_id {}? Pick delete again to go ahead", id));
+            }
+            self.pending_delete = Some(id);
+            return;
+        }
+
+        self.commit("delete a transaction", |company| {
+            company.remove_transaction(&id, true)?;
+            Ok(format!("Deleted transaction_id {}", id))
+        });
+    }
+
+    pub fn edit_transaction(&mut self, input: &str) {
+        // reads "<id> <field> <value>" for the date, debit, credit, amount,
+        // memo, payee, tag or untag, "<id> meta <key> [value]" where leaving
+        // out the value removes the key, or "<id> status <account>
+        // [uncleared|pending|cleared]" where leaving out the status moves it
+        // on to the next one.
+        // reconciled transactions ask for the same edit to be entered twice
+        let input = input.trim().to_string();
+        let confirmed = self.pending_edit.as_ref() == Some(&input);
+        self.pending_edit = None;
+
+        let words = query::split_words(&input);
+        if words.len() < 3 {
+            self.messages.push("Enter: <transaction_id> <date|debit|credit|amount|memo|payee|tag|untag> <value>, <transaction_id> meta <key> [value] or <transaction_id> status <account> [status]".to_string());
+            return;
+        }
+        let (id, field, values) = (&words[0], &words[1][..], &words[2..]);
+
+        let reconciled = self.company
+            .get_transaction_by_id(id)
+            .is_some_and(|transaction| transaction.is_reconciled());
+        if reconciled && !confirmed {
+            self.messages.push(format!(
+                "Warning: transaction_id {} has been reconciled with the bank. Enter the same edit again to make it anyway", id
+            ));
+            self.pending_edit = Some(input);
+            return;
+        }
+
+        self.commit("edit a transaction", |company| {
+            let mut transaction = company
+                .get_transaction_by_id(id)
+                .cloned()
+                .ok_or_else(|| format!("transaction_id {} cannot be found", id))?;
+            let value = values.join(" ");
+            let mut message = format!("Changed the {} of transaction_id {}", field, id);
+
+            match field {
+                "date" => {
+                    transaction.date = NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                        .map_err(|_| format!("{} is not a date", value))?;
+                },
+                "debit" => {
+                    let debit = company.get_open_account_id(&value)?;
+                    transaction.set_debit(&debit);
+                },
+                "credit" => {
+                    let credit = company.get_open_account_id(&value)?;
+                    transaction.set_credit(&credit);
+                },
+                "amount" => {
+                    if value.parse::<f64>().map_or(true, |amount| amount <= 0.0) {
+                        return Err(format!("{} is not a positive amount", value).into());
+                    }
+                    transaction.set_amount(&value);
+                },
+                "memo" => {
+                    transaction.set_memo(&value);
+                },
+                "payee" => {
+                    transaction.set_payee(&value);
+                },
+                "tag" => {
+                    transaction.add_tag(&value);
+                    message = format!("Tagged transaction_id {} #{}", id, value.trim_start_matches('#'));
+                },
+                "untag" => {
+                    transaction.remove_tag(&value)?;
+                    message = format!("Removed the tag {} from transaction_id {}", value, id);
+                },
+                "meta" => {
+                    let key = &values[0];
+                    let value = values[1..].join(" ");
+                    transaction.set_metadata(key, &value);
+                    message = if value.is_empty() {
+                        format!("Removed {} from transaction_id {}", key, id)
+                    }
+                    else {
+                        format!("Set {} of transaction_id {} to {}", key, id, value)
+                    };
+                },
+                "status" => {
+                    let account = company
+                        .find_account_by_path(&values[0])
+                        .map(|account| account.id.clone())
+                        .ok_or_else(|| format!("no account matches {}", values[0]))?;
+                    let status = match (transaction.get_status(&account), values.get(1)) {
+                        ("", _) => return Err(format!("transaction_id {} does not post to {}", id, values[0]).into()),
+                        (_, Some(status)) => status.to_string(),
+                        ("uncleared", None) => "pending".to_string(),
+                        ("pending", None) => "cleared".to_string(),
+                        (_, None) => "uncleared".to_string(),
+                    };
+                    // only reconciling marks a posting reconciled
+                    if !matches!(&status[..], "uncleared" | "pending" | "cleared") {
+                        return Err(format!("{} is not uncleared, pending or cleared", status).into());
+                    }
+                    transaction.set_status(&account, &status);
+                    message = format!("Marked the {} posting of transaction_id {} {}", company.get_account_path(&account), id, status);
+                },
+                _ => return Err(format!("cannot edit the {} of a transaction", field).into()),
+            }
+
+            company.update_transaction(transaction, confirmed)?;
+            Ok(message)
+        });
+    }
+
+    pub fn change_account(&mut self, input: &str) {
+        // reads one of
+        // rename <id> <name>, move <id> <parent_id>, merge <id> <into_id>,
+        // close <id>, reopen <id>, cash <id> on|off, category <id> <category>
+        // or delete <id>
+        let parts = input.split_whitespace().collect::<Vec<&str>>();
+
+        let (command, id, rest) = match parts[..] {
+            [command, id, ref rest @ ..] => (command, id.to_string(), rest.join(" ")),
+            _ => {
+                self.messages.push("Expected: rename, move, merge, close, reopen, cash, category or delete followed by an account_id".to_string());
+                return;
+            }
+        };
+
+        match command {
+            "rename" => self.commit("rename an account", |company| {
+                company.rename_account(&id, &rest)?;
+                Ok(format!("Renamed account_id {} to {}", id, rest))
+            }),
+            "move" => self.commit("move an account", |company| {
+                company.move_account(&id, &rest)?;
+                Ok(format!("Moved account_id {} under {}", id, rest))
+            }),
+            "merge" => self.commit("merge an account", |company| {
+                company.merge_account(&id, &rest)?;
+                Ok(format!("Merged account_id {} into {}", id, rest))
+            }),
+            "close" => self.commit("close an account", |company| {
+                company.close_account(&id)?;
+                Ok(format!("Closed account_id {}", id))
+            }),
+            "reopen" => self.commit("reopen an account", |company| {
+                company.reopen_account(&id)?;
+                Ok(format!("Reopened account_id {}", id))
+            }),
+            "cash" => self.commit("mark a cash account", |company| {
+                let cash = match &rest[..] {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err("expected: cash <id> on or off".into()),
+                };
+                company.set_account_cash(&id, cash)?;
+                Ok(format!("Account_id {} is {}a cash account", id, if cash { "" } else { "not " }))
+            }),
+            "category" => self.commit("set a cash flow category", |company| {
+                company.set_account_category(&id, &rest)?;
+                Ok(format!("Account_id {} has the cash flow category {}", id, rest))
+            }),
+            "delete" => self.commit("delete an account", |company| {
+                company.remove_account(&id)?;
+                Ok(format!("Deleted account_id {}", id))
+            }),
+            _ => {
+                self.messages.push(format!("Unknown account command {}", command));
+                false
+            },
+        };
+    }
+
+    pub fn get_chart_nodes(&self) -> Vec<(&Account, usize)> {
+        // the open accounts in tree order, leaving out those under a collapsed parent
+        let mut nodes = Vec::new();
+        let mut hidden_below: Option<usize> = None;
+
+        for (account, depth) in self.company.get_account_tree() {
+            if let Some(hidden_depth) = hidden_below {
+                if depth > hidden_depth {
+                    continue;
+                }
+                hidden_below = None;
+            }
+
+            if account.closed || self.chart_collapsed.contains(&account.id) {
+                hidden_below = Some(depth);
+            }
+            if !account.closed {
+                nodes.push((account, depth));
+            }
+        }
+
+        nodes
+    }
+
+    pub fn get_selected_account(&self) -> Option<String> {
+        self.get_chart_nodes()
+            .get(self.chart_selected)
+            .map(|(account, _)| account.id.clone())
+    }
+
+    pub fn chart_select_next(&mut self) {
+        if self.chart_selected + 1 < self.get_chart_nodes().len() {
+            self.chart_selected += 1;
+        }
+    }
+
+    pub fn chart_select_previous(&mut self) {
+        self.chart_selected = self.chart_selected.saturating_sub(1);
+    }
+
+    pub fn chart_expand(&mut self) {
+        if let Some(id) = self.get_selected_account() {
+            self.chart_collapsed.retain(|collapsed| *collapsed != id);
+        }
+    }
+
+    pub fn chart_collapse(&mut self) {
+        // collapses the selected account, or moves up to its parent
+        // when there is nothing to collapse
+        let id = match self.get_selected_account() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let has_children = !self.company.get_children(&id).is_empty();
+        if has_children && !self.chart_collapsed.contains(&id) {
+            self.chart_collapsed.push(id);
+            return;
+        }
+
+        let parent = self.company.get_acccount_by_id(&id).map(|account| account.parent.clone());
+        if let Some(parent) = parent {
+            let position = self.get_chart_nodes()
+                .iter()
+                .position(|(account, _)| account.id == parent);
+            if let Some(position) = position {
+                self.chart_selected = position;
+            }
+        }
+    }
+
+    pub fn chart_open_register(&mut self) {
+        if let Some(id) = self.get_selected_account() {
+            self.set_query(&format!("account:{}", id));
+            self.focus = Focus::Register;
+        }
+    }
+
+    pub fn chart_begin(&mut self, action: &str) {
+        // waits for the name to use when adding or renaming an account
+        if self.get_selected_account().is_some() {
+            self.chart_action = Some(action.to_string());
+            self.input_mode = InputMode::Editing;
+        }
+    }
+
+    pub fn submit_chart_action(&mut self, input: &str) {
+        let id = match self.get_selected_account() {
+            Some(id) => id,
+            None => return self.change_account(input),
+        };
+
+        match self.chart_action.take().as_deref() {
+            Some("add") => {
+                self.commit("add an account", |company| {
+                    let child = company.create_account(&id, input, "")?;
+                    Ok(format!("Added account_id {} named {}", child, input.trim()))
+                });
+                self.chart_collapsed.retain(|collapsed| *collapsed != id);
+                self.input_mode = InputMode::Normal;
+            },
+            Some("rename") => {
+                self.commit("rename an account", |company| {
+                    company.rename_account(&id, input)?;
+                    Ok(format!("Renamed account_id {} to {}", id, input.trim()))
+                });
+                self.input_mode = InputMode::Normal;
+            },
+            _ => self.change_account(input),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// the reference expense report service.
+// it answers the requests Money sends when the expense report is
+// built with "service", speaking the JSON protocol and the original bare path replies
+
+use clap::Parser;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use money::company::Company;
+use money::filter::{Comparison, Condition, Filter};
+use money::protocol::{self, Artifact, Request, Response};
+use money::report::ExpenseReport;
+
+
+#[derive(Debug, Parser)]
+#[clap(about = "Serve expense reports to Money over ZMQ")]
+struct Args {
+    #[clap(short = 'e',
+        long,
+        default_value = "tcp://127.0.0.1:6000",
+        help = "Address to bind the REP socket to")]
+    endpoint: String,
+
+    #[clap(short = 'o',
+        long,
+        help = "Directory to write reports to. Defaults to the system temporary directory")]
+    output: Option<String>,
+
+    #[clap(long, help = "Answer one request and exit")]
+    once: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+
+    let args = Args::parse();
+    let output = args.output.map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+
+    let ctx = zmq::Context::new();
+    let socket = ctx.socket(zmq::REP)?;
+    socket.bind(&args.endpoint)?;
+    println!("Serving expense reports on {}, writing to {}", args.endpoint, output.display());
+
+    loop {
+        let mut msg = zmq::Message::new();
+        socket.recv(&mut msg, 0)?;
+
+        let reply = match msg.as_str() {
+            Some(payload) => answer(payload, &output),
+            None => serde_json::to_string(&Response::error("bad_request", "the request is not text"))?,
+        };
+        socket.send(&reply[..], 0)?;
+
+        if args.once {
+            return Ok(());
+        }
+    }
+}
+
+fn answer(payload: &str, output: &Path) -> String {
+    // clients that send the bare database get the bare path back
+    let request = match Request::parse(payload) {
+        Ok(request) => request,
+        Err(err) => return to_reply(&Response::error("bad_request", &err.to_string())),
+    };
+
+    let response = handle(&request, output);
+    println!("{} {} -> {}", request.r#type, request.params.format, response.status);
+
+    if request.is_legacy() {
+        return response.artifact.and_then(|artifact| artifact.path).unwrap_or_default();
+    }
+
+    to_reply(&response)
+}
+
+fn handle(request: &Request, output: &Path) -> Response {
+    if request.version > protocol::VERSION {
+        let message = format!("version {} is not supported, the newest is {}", request.version, protocol::VERSION);
+        return Response::error("unsupported_version", &message);
+    }
+    if request.r#type != "expense_report" {
+        return Response::error("unknown_type", &format!("{} is not a known request", request.r#type));
+    }
+
+    match expense_report(request, output) {
+        Ok(artifact) => Response::ok(artifact),
+        Err(err) => Response::error("internal", &err.to_string()),
+    }
+}
+
+fn expense_report(request: &Request, output: &Path) -> Result<Artifact, Box<dyn Error>> {
+    let params = &request.params;
+    let company: Company = serde_json::from_value(request.database.clone())?;
+
+    // missing dates fall back to the period holding the latest transaction
+    let (first, last) = company.get_current_period(&params.period);
+    let start = params.start.unwrap_or(first);
+    let end = params.end.unwrap_or(last);
+
+    let mut filter = Filter::new();
+    if !params.accounts.is_empty() {
+        filter.add_condition(Condition::Account(Comparison::Equal, "requested".to_string(), params.accounts.clone()));
+    }
+
+    let report = ExpenseReport::generate(&company.filtered(&filter), start, end)?;
+    let path = report.export(&params.format, None, output)?;
+
+    Ok(Artifact {
+        path: Some(path.display().to_string()),
+        content: None,
+        format: params.format.clone(),
+    })
+}
+
+fn to_reply(response: &Response) -> String {
+    serde_json::to_string(response).unwrap_or_else(|_| "{\"version\":1,\"status\":\"error\"}".to_string())
+}
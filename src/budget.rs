@@ -0,0 +1,138 @@
+use chrono::prelude::*;
+use std::cmp::Ordering;
+use crate::company::Company;
+use crate::account::Account;
+
+// Budgets set a planned amount for an Account over a recurring period
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Budget {
+    pub id: String,
+    pub account: String,
+    pub amount: String,
+    pub period: String
+    // period is one of "month", "quarter" or "year"
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget::new()
+    }
+}
+
+impl Budget {
+
+    pub fn new() -> Self {
+        Budget {
+            id: "".to_string(),
+            account: "".to_string(),
+            amount: "".to_string(),
+            period: "month".to_string()
+        }
+    }
+
+    pub fn cmp_id(&self, another: &Budget) -> Ordering {
+
+        let first = self.id.parse::<usize>().expect("Bad id");
+        let second = another.id.parse::<usize>().expect("Bad id");
+
+        first.cmp(&second)
+    }
+
+    pub fn set_id_in_company(&mut self, company: &mut Company) -> &mut Self {
+        // set the id to be the next highest one in the company
+        let current_max = company.max_id_budget().unwrap_or(0);
+        self.id = (current_max + 1).to_string();
+
+        self
+    }
+
+    pub fn set_account(&mut self, account: &str) -> &mut Self {
+        // sets the account the budget applies to
+        self.account = account.to_string();
+
+        self
+    }
+
+    pub fn set_amount(&mut self, amount: &str) -> &mut Self {
+        // sets the planned amount for one period
+        self.amount = amount.to_string();
+
+        self
+    }
+
+    pub fn set_period(&mut self, period: &str) -> &mut Self {
+        // only accepts the known periods
+        match period {
+            "month" | "quarter" | "year" => self.period = period.to_string(),
+            _ => {}
+        };
+
+        self
+    }
+
+    pub fn get_amount(&self) -> f64 {
+        self.amount.parse::<f64>().unwrap_or(0.0)
+    }
+
+    pub fn get_amount_for_period(&self, period: &str) -> f64 {
+        // scale the budget so it can be compared against another period length
+        let months = |period: &str| match period {
+            "year" => 12.0,
+            "quarter" => 3.0,
+            _ => 1.0,
+        };
+
+        self.get_amount() / months(&self.period) * months(period)
+    }
+}
+
+pub fn period_bounds(period: &str, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    // months, quarters and years are calendar aligned.
+    // unknown periods fall back to a month
+    let (start_month, length) = match period {
+        "year" => (1, 12),
+        "quarter" => (((date.month() - 1) / 3) * 3 + 1, 3),
+        _ => (date.month(), 1),
+    };
+
+    let start = NaiveDate::from_ymd(date.year(), start_month, 1);
+
+    let mut end_year = date.year();
+    let mut end_month = start_month + length;
+    if end_month > 12 {
+        end_month -= 12;
+        end_year += 1;
+    }
+    let end = NaiveDate::from_ymd(end_year, end_month, 1).pred();
+
+    (start, end)
+}
+
+// One row of the budget vs actual report
+#[derive(Debug, Clone)]
+pub struct BudgetLine<'a> {
+    pub account: &'a Account,
+    pub depth: usize,
+    pub actual: f64,
+    pub budget: f64,
+}
+
+impl<'a> BudgetLine<'a> {
+
+    pub fn remaining(&self) -> f64 {
+        self.budget - self.actual
+    }
+
+    pub fn percent_used(&self) -> Option<f64> {
+        if self.budget == 0.0 {
+            None
+        }
+        else {
+            Some(self.actual / self.budget * 100.0)
+        }
+    }
+
+    pub fn is_overspent(&self) -> bool {
+        self.budget > 0.0 && self.actual > self.budget
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::prelude::*;
+use std::error::Error;
+use crate::company::Company;
+use crate::config::Config;
+
+// the cash flow categories in the order they are reported
+pub const CATEGORIES: [&str; 3] = ["operating", "investing", "financing"];
+
+// One counter-account's share of the cash that moved in the period
+#[derive(Debug, Clone)]
+pub struct CashFlowLine {
+    pub category: String,
+    pub account: String,
+    pub inflow: f64,
+    pub outflow: f64,
+}
+
+// A CashFlow follows the money in and out of the cash accounts over a period
+#[derive(Debug, Clone)]
+pub struct CashFlow {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub opening: f64,
+    pub lines: Vec<CashFlowLine>,
+}
+
+impl CashFlow {
+
+    pub fn generate(company: &Company, start: NaiveDate, end: NaiveDate) -> Result<Self, Box<dyn Error>> {
+        // money moving between two cash accounts is a transfer and left out
+        let cash_ids = company.get_cash_account_ids();
+
+        if cash_ids.is_empty() {
+            return Err("no accounts are marked as cash".into());
+        }
+
+        let mut opening = 0.0;
+        let mut lines = Vec::<CashFlowLine>::new();
+
+        for transaction in &company.transactions {
+            if transaction.date > end {
+                continue;
+            }
+
+            let debit_is_cash = cash_ids.contains(&transaction.debit);
+            let credit_is_cash = cash_ids.contains(&transaction.credit);
+            let amount = transaction.amount.parse::<f64>().unwrap_or(0.0);
+
+            let (counter, inflow, outflow) = match (debit_is_cash, credit_is_cash) {
+                (true, false) => (&transaction.credit, amount, 0.0),
+                (false, true) => (&transaction.debit, 0.0, amount),
+                _ => continue,
+            };
+
+            if transaction.date < start {
+                opening += inflow - outflow;
+                continue;
+            }
+
+            let category = company.get_account_category(counter);
+            let account = company.get_account_path(counter);
+
+            match lines.iter_mut().find(|line| line.account == account) {
+                Some(line) => {
+                    line.inflow += inflow;
+                    line.outflow += outflow;
+                },
+                None => lines.push(CashFlowLine { category, account, inflow, outflow }),
+            }
+        }
+
+        lines.sort_by(|a, b| a.account.cmp(&b.account));
+
+        Ok(CashFlow { start, end, opening, lines })
+    }
+
+    pub fn get_lines(&self, category: &str) -> Vec<&CashFlowLine> {
+        self.lines.iter().filter(|line| line.category == category).collect()
+    }
+
+    pub fn get_net(&self, category: &str) -> f64 {
+        self.get_lines(category)
+            .iter()
+            .fold(0.0, |total, line| total + line.inflow - line.outflow)
+    }
+
+    pub fn get_total_inflow(&self) -> f64 {
+        self.lines.iter().fold(0.0, |total, line| total + line.inflow)
+    }
+
+    pub fn get_total_outflow(&self) -> f64 {
+        self.lines.iter().fold(0.0, |total, line| total + line.outflow)
+    }
+
+    pub fn get_closing(&self) -> f64 {
+        self.opening + self.get_total_inflow() - self.get_total_outflow()
+    }
+
+    pub fn to_rows(&self) -> Vec<(String, Option<f64>)> {
+        // each line's label and the amount that closes it, if any
+        let mut rows = Vec::new();
+
+        rows.push((format!("{:<32}", "Starting cash"), Some(self.opening)));
+
+        for category in CATEGORIES {
+            let lines = self.get_lines(category);
+            if lines.is_empty() {
+                continue;
+            }
+
+            let mut title = category.to_string();
+            title[..1].make_ascii_uppercase();
+            rows.push(("".to_string(), None));
+            rows.push((format!("{} activities", title), None));
+
+            for line in lines {
+                if line.inflow != 0.0 {
+                    rows.push((format!("  in from {:<23}", line.account), Some(line.inflow)));
+                }
+                if line.outflow != 0.0 {
+                    rows.push((format!("  out to {:<24}", line.account), Some(0.0 - line.outflow)));
+                }
+            }
+            rows.push((format!("{:<32}", format!("Net {} cash", category)), Some(self.get_net(category))));
+        }
+
+        rows.push(("".to_string(), None));
+        rows.push((format!("{:<32}", "Total inflows"), Some(self.get_total_inflow())));
+        rows.push((format!("{:<32}", "Total outflows"), Some(0.0 - self.get_total_outflow())));
+        rows.push((format!("{:<32}", "Ending cash"), Some(self.get_closing())));
+
+        rows
+    }
+
+    pub fn to_lines(&self, config: &Config) -> Vec<String> {
+        // the statement as plain text for the command line
+        let mut text = Vec::new();
+
+        text.push(format!("Cash Flow Statement, {} to {}", config.format_date(self.start), config.format_date(self.end)));
+        for (label, amount) in self.to_rows() {
+            match amount {
+                Some(amount) => text.push(format!("{}{:>12}", label, config.format_amount(amount))),
+                None => text.push(label),
+            }
+        }
+
+        text
+    }
+}
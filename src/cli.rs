@@ -0,0 +1,174 @@
+// the command line version of Money.
+// each command loads the database, prints its result and exits
+
+use chrono::prelude::*;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::company::Company;
+use crate::config::{get_default_path, Command, Config, ConfigAction};
+use crate::query::Query;
+use crate::cashflow::CashFlow;
+use crate::report::ExpenseReport;
+use crate::server::Server;
+use crate::keymap::Keymap;
+use crate::manual::{self, Topic};
+
+
+pub fn run(command: Command, config: &Config) -> Result<(), Box<dyn Error>> {
+
+    // commands about the configuration do not need the database
+    match command {
+        Command::Services => {
+            for service in &config.services.services {
+                println!("{}", service.describe());
+            }
+            return Ok(());
+        },
+        Command::Help { topic } => {
+            return print_help(&topic.join(" "), config);
+        },
+        Command::Tutorial => {
+            return Err("the tutorial runs in the interface, start it with money tutorial".into());
+        },
+        Command::Config { action: ConfigAction::Show } => {
+            match &config.config_path {
+                Some(path) => println!("# read from {}", path.display()),
+                None => println!("# no settings file, expected at {}",
+                    get_default_path().map_or("an unknown location".to_string(), |path| path.display().to_string())),
+            }
+            print!("{}", toml::to_string(&config.to_file())?);
+            return Ok(());
+        },
+        _ => {},
+    }
+
+    let company = Company::default().load(&config.database)?;
+
+    match command {
+        Command::Query { query, balance } => {
+            let query = Query::parse(&company, &query.join(" "))?;
+            if balance {
+                print_balances(&company, &query, config);
+            }
+            else {
+                print_register(&company, &query, config);
+            }
+        },
+        Command::CashFlow { start, end, period } => {
+            let (start, end) = get_dates(&company, start, end, &period)?;
+
+            for line in CashFlow::generate(&company, start, end)?.to_lines(config) {
+                println!("{}", line);
+            }
+        },
+        Command::Services | Command::Help { .. } | Command::Config { .. } => {},
+        Command::Tutorial => {},
+        Command::Serve { endpoint, publish } => {
+            let mut server = Server::new(company, &config.database)?;
+            server.run(&zmq::Context::new(), &endpoint, publish.or_else(|| config.publish.clone()).as_deref())?;
+        },
+        Command::Expenses { start, end, period, format, output } => {
+            let (start, end) = get_dates(&company, start, end, &period)?;
+            let report = ExpenseReport::generate(&company, start, end)?;
+
+            match format {
+                Some(format) => {
+                    let directory = config.database.parent().unwrap_or_else(|| Path::new("."));
+                    let output = output.map(PathBuf::from);
+                    let path = report.export(&format.to_lowercase(), output.as_deref(), directory)?;
+                    println!("Exported the expense report to {}", path.display());
+                },
+                None => {
+                    for line in report.to_lines(30, config) {
+                        println!("{}", line);
+                    }
+                },
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn get_dates(company: &Company, start: Option<String>, end: Option<String>, period: &str) -> Result<(NaiveDate, NaiveDate), Box<dyn Error>> {
+    // dates left out come from the period holding the latest transaction
+    let (first, last) = company.get_current_period(period);
+    let start = match start {
+        Some(start) => NaiveDate::parse_from_str(&start, "%Y-%m-%d")?,
+        None => first,
+    };
+    let end = match end {
+        Some(end) => NaiveDate::parse_from_str(&end, "%Y-%m-%d")?,
+        None => last,
+    };
+
+    Ok((start, end))
+}
+
+fn print_register(company: &Company, query: &Query, config: &Config) {
+    let mut total = 0.0;
+    let mut lines = Vec::<String>::new();
+
+    for transaction in query.run(company) {
+        let amount = transaction.amount.parse::<f64>().unwrap_or(0.0);
+        total += amount;
+
+        lines.push(format!(
+            "{} {:>4} {:<24} {:<20} {:<20} {:>10}",
+            config.format_date(transaction.date),
+            transaction.id,
+            transaction.memo,
+            company.get_account_path(&transaction.debit),
+            company.get_account_path(&transaction.credit),
+            config.format_amount(amount)
+        ));
+    }
+
+    // the total lines up under the amounts, however wide the rows came out
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    for line in lines {
+        println!("{}", line);
+    }
+    println!("{:>width$}", config.format_amount(total), width = width);
+}
+
+fn print_balances(company: &Company, query: &Query, config: &Config) {
+    for (name, balance) in query.get_balances(company, None, None) {
+        println!("{:<30}{:>12}", name, config.format_amount(balance));
+    }
+}
+
+fn print_help(terms: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    // a topic by name, or the topics holding every word when none is named
+    let keymap = Keymap::from_settings(&config.keymap)?;
+
+    let topics = match manual::get_topic(terms) {
+        Some(topic) => vec![topic],
+        None => manual::search(terms, &keymap),
+    };
+
+    match topics[..] {
+        [] => Err(format!(
+            "nothing in the manual matches {}, see money help for the topics or money <command> --help for a command", terms
+        ).into()),
+        [topic] if !terms.is_empty() => {
+            println!("{}\n", topic.title);
+            for line in manual::get_body(topic, &keymap) {
+                println!("{}", line);
+            }
+            Ok(())
+        },
+        _ => {
+            print_topics(&topics);
+            Ok(())
+        },
+    }
+}
+
+fn print_topics(topics: &[&Topic]) {
+    for topic in topics {
+        println!("{:<14}{}", topic.name, topic.title);
+    }
+    println!("\nRead one with money help <topic>");
+}
@@ -0,0 +1,345 @@
+// the command line runs commands typed into the input after a colon, such as
+// :add 2014-11-28 Expenses:Food Assets 15 "taco bell", :reg Food or
+// :bal --period month. their output goes to the messages.
+// see "Command line" in the README
+
+use crate::app::{App, Focus};
+use crate::manual::{self, TOPICS};
+use crate::query::{Query, split_words};
+
+// name, usage and what it does
+pub const COMMANDS: [(&str, &str, &str); 10] = [
+    ("add", "add [YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>]", "Add a transaction"),
+    ("reg", "reg <account or query>", "List the register of an account"),
+    ("bal", "bal [--period month|quarter|year] [account or query]", "List the balances, over a period if given"),
+    ("del", "del <transaction_id>", "Delete a transaction"),
+    ("query", "query [query]", "Filter the reports, or clear the filter"),
+    ("undo", "undo", "Undo the last change"),
+    ("redo", "redo", "Redo the last undone change"),
+    ("help", "help [topic or words]", "Open the manual"),
+    ("history", "history", "List the commands run before"),
+    ("commands", "commands", "List the commands"),
+];
+
+const PERIODS: [&str; 3] = ["month", "quarter", "year"];
+
+// how many commands the state file keeps
+const MAX_HISTORY: usize = 100;
+
+// how many matches a completion lists
+const MAX_MATCHES: usize = 8;
+
+pub fn run(app: &mut App, input: &str) {
+    let line = input.trim().trim_start_matches(':').trim();
+    if line.is_empty() {
+        return;
+    }
+
+    // the messages scroll to the command, so its output is in view
+    app.messages_scroll = app.messages.len();
+    app.messages.push(format!(":{}", line));
+
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+
+    // a typo is not kept in the history for Up to bring back
+    if !COMMANDS.iter().any(|(command, ..)| *command == name) {
+        app.messages.push(format!("{} is not a command, enter :commands to list them", name));
+        return;
+    }
+    remember(app, line);
+
+    match name {
+        "add" => app.add_transaction(rest),
+        "reg" => print_register(app, rest),
+        "bal" => print_balances(app, rest),
+        "del" => app.delete_transaction(rest),
+        "query" => app.set_query(rest),
+        "undo" => app.undo(),
+        "redo" => app.redo(),
+        "help" => {
+            if app.focus != Focus::Help {
+                app.toggle_help();
+            }
+            match manual::get_topic(rest) {
+                Some(topic) => app.help_topic = Some(topic),
+                None if !rest.is_empty() => app.search_help(rest),
+                None => {},
+            }
+        },
+        "history" => {
+            let history = &app.state.command_history;
+            let lines = history
+                .iter()
+                .enumerate()
+                .skip(history.len().saturating_sub(20))
+                .map(|(i, command)| format!("{:>3} :{}", i + 1, command))
+                .collect::<Vec<String>>();
+            app.messages.extend(lines);
+        },
+        "commands" => {
+            for (_, usage, description) in COMMANDS {
+                app.messages.push(format!(":{}", usage));
+                app.messages.push(format!("    {}", description));
+            }
+        },
+        _ => {},
+    }
+}
+
+fn remember(app: &mut App, line: &str) {
+    let history = &mut app.state.command_history;
+    if history.last().map(|last| &last[..]) != Some(line) {
+        history.push(line.to_string());
+    }
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+
+    if let Err(err) = app.state.save() {
+        app.messages.push(format!("Could not save the command history: {}", err));
+    }
+}
+
+fn print_register(app: &mut App, input: &str) {
+    if input.is_empty() {
+        app.messages.push("Enter: :reg <account or query>".to_string());
+        return;
+    }
+
+    let query = match Query::parse(&app.company, &App::get_register_terms(input)) {
+        Ok(query) => query,
+        Err(err) => {
+            app.messages.push(format!("Could not read the query: {}", err));
+            return;
+        },
+    };
+
+    let register = query.run_register(&app.company);
+    if let Some(tutorial) = &mut app.tutorial {
+        tutorial.list_register(register.iter().map(|(transaction, _, _)| &transaction.id[..]));
+    }
+
+    let lines = register
+        .into_iter()
+        .map(|(transaction, amount, total)| format!(
+            "{} {:>4} {:<14.14} {:>9} {:>9}",
+            app.format_date(transaction.date),
+            transaction.id,
+            transaction.memo,
+            app.format_amount(amount),
+            app.format_amount(total)
+        ))
+        .collect::<Vec<String>>();
+
+    if lines.is_empty() {
+        app.messages.push(format!("No transactions match {}", query.describe()));
+    }
+    else {
+        app.messages.push(format!("Register for {}", query.describe()));
+        app.messages.extend(lines);
+    }
+}
+
+fn print_balances(app: &mut App, input: &str) {
+    // balances roll up through the account tree like the balance sheet
+    let mut period = None;
+    let mut terms = Vec::new();
+    let mut words = input.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "--period" | "-p" => period = words.next(),
+            _ => terms.push(word),
+        }
+    }
+    if period.is_some_and(|period| !PERIODS.contains(&period)) {
+        app.messages.push("Enter: :bal [--period month|quarter|year] [account or query]".to_string());
+        return;
+    }
+
+    let query = match Query::parse(&app.company, &App::get_register_terms(&terms.join(" "))) {
+        Ok(query) => query,
+        Err(err) => {
+            app.messages.push(format!("Could not read the query: {}", err));
+            return;
+        },
+    };
+
+    let (start, end) = match period {
+        Some(period) => {
+            let (start, end) = app.company.get_current_period(period);
+            app.messages.push(format!(
+                "Balances for the {} from {} to {}", period, app.format_date(start), app.format_date(end)
+            ));
+            (Some(start), Some(end))
+        },
+        None => {
+            app.messages.push("Balances".to_string());
+            (None, None)
+        },
+    };
+
+    for (name, balance) in query.get_balances(&app.company, start, end) {
+        app.messages.push(format!("{:<30}{:>12}", name, app.format_amount(balance)));
+    }
+}
+
+pub fn complete(app: &mut App) {
+    // completes the last word of the input: a command name first, then
+    // account paths, payees after payee:, manual topics after help and
+    // periods after --period. several matches complete as far as they
+    // agree and are listed in the messages
+    let start = get_last_word(&app.input);
+    let words = split_words(&app.input[..start]);
+    let word = app.input[start..].replace('"', "");
+
+    let (prefix, partial) = match word.split_once(':') {
+        Some(("", partial)) if words.is_empty() => (":".to_string(), partial.to_string()),
+        Some((kind, partial)) if kind == "payee" || kind == "account" => (format!("{}:", kind), partial.to_string()),
+        _ => (String::new(), word.clone()),
+    };
+
+    let command = words.first().map(|word| word.trim_start_matches(':'));
+    let candidates = match (command, words.last()) {
+        (None, _) if app.reads_commands() => COMMANDS.iter().map(|(name, ..)| name.to_string()).collect(),
+        _ if prefix == "payee:" => get_payees(app),
+        (Some("help"), _) => TOPICS.iter().map(|topic| topic.name.to_string()).collect(),
+        (_, Some(last)) if last == "--period" || last == "-p" => PERIODS.iter().map(|period| period.to_string()).collect(),
+        // closed accounts can still be looked up, but not posted to
+        (Some("reg" | "bal" | "query"), _) => get_account_paths(app, false),
+        _ if !app.input.starts_with(':') && matches!(app.focus, Focus::Register | Focus::Filter) => get_account_paths(app, false),
+        _ => get_account_paths(app, true),
+    };
+
+    // a match starts with what was typed, or has a part of its path that does
+    let lowercase = partial.to_lowercase();
+    let matches = candidates
+        .into_iter()
+        .filter(|candidate| {
+            let candidate = candidate.to_lowercase();
+            candidate.starts_with(&lowercase) || candidate.contains(&format!(":{}", lowercase))
+        })
+        .collect::<Vec<String>>();
+
+    let completed = match &matches[..] {
+        [] => return,
+        [only] => format!("{} ", quote(only, true)),
+        _ => {
+            let common = get_common_prefix(&matches);
+            let shown = matches.iter().take(MAX_MATCHES).cloned().collect::<Vec<String>>().join(", ");
+            if matches.len() > MAX_MATCHES {
+                app.messages.push(format!("Matches: {} and {} more", shown, matches.len() - MAX_MATCHES));
+            }
+            else {
+                app.messages.push(format!("Matches: {}", shown));
+            }
+            if common.len() < partial.len() || !common.to_lowercase().contains(&lowercase) {
+                return;
+            }
+            quote(&common, false)
+        },
+    };
+
+    app.input.truncate(start);
+    app.input.push_str(&prefix);
+    app.input.push_str(&completed);
+}
+
+pub fn recall(app: &mut App, older: bool) {
+    // Up and Down walk the command history, newest first, on the command
+    // line only
+    let history = &app.state.command_history;
+    if history.is_empty() || !app.input.starts_with(':') {
+        return;
+    }
+
+    let index = match (app.recalled, older) {
+        (None, true) => history.len() - 1,
+        (None, false) => return,
+        (Some(index), true) => index.saturating_sub(1),
+        (Some(index), false) if index + 1 < history.len() => index + 1,
+        (Some(_), false) => {
+            app.recalled = None;
+            app.input = ":".to_string();
+            return;
+        },
+    };
+
+    app.input = format!(":{}", history[index]);
+    app.recalled = Some(index);
+}
+
+fn get_last_word(input: &str) -> usize {
+    // where the last word starts, a space inside quotes does not end a word
+    let mut start = 0;
+    let mut quoted = false;
+
+    for (i, c) in input.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => start = i + c.len_utf8(),
+            _ => {},
+        }
+    }
+
+    start
+}
+
+fn get_common_prefix(matches: &[String]) -> String {
+    // compared without case, keeping the case of the first match
+    let first = &matches[0];
+    let mut length = first.len();
+    for other in &matches[1..] {
+        length = first
+            .char_indices()
+            .zip(other.chars())
+            .take_while(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()))
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(length);
+    }
+
+    first[..length].to_string()
+}
+
+fn quote(text: &str, finished: bool) -> String {
+    // words with spaces go in quotes, left open if more may follow
+    if !text.contains(' ') {
+        text.to_string()
+    }
+    else if finished {
+        format!("\"{}\"", text)
+    }
+    else {
+        format!("\"{}", text)
+    }
+}
+
+fn get_account_paths(app: &App, open: bool) -> Vec<String> {
+    let accounts = if open {
+        app.company.get_open_accounts()
+    }
+    else {
+        app.company.accounts.iter().collect()
+    };
+
+    let mut paths = accounts
+        .iter()
+        .map(|account| app.company.get_account_path(&account.id))
+        .collect::<Vec<String>>();
+    paths.sort();
+
+    paths
+}
+
+fn get_payees(app: &App) -> Vec<String> {
+    let mut payees = Vec::<String>::new();
+    for transaction in &app.company.transactions {
+        if !transaction.payee.is_empty() && !payees.contains(&transaction.payee) {
+            payees.push(transaction.payee.clone());
+        }
+    }
+    payees.sort();
+
+    payees
+}
@@ -1,24 +1,32 @@
 use chrono::prelude::*;
 use std::error::Error;
 use std::io;
-use std::path::{Path, PathBuf};
-use zmq;
+use std::path::Path;
+use std::borrow::Cow;
 
 use std::cmp::Ordering;
 
 use crate::file_io;
 use crate::account::Account;
 use crate::transaction::Transaction;
+use crate::budget::{self, Budget, BudgetLine};
+use crate::recurrence::Recurrence;
+use crate::filter::Filter;
+use crate::cashflow;
 
 // see https://www.youtube.com/watch?v=hIi_UlyIPMg
 // on using serde to derive structs from json
 
 
 // This the primary object that contains the Accounts and Tranactions
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Company {
     pub accounts: Vec<Account>,
-    pub transactions: Vec<Transaction>
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+    #[serde(default)]
+    pub recurrences: Vec<Recurrence>
 }
 
 impl Default for Company {
@@ -26,6 +34,8 @@ impl Default for Company {
         Company {
             accounts: Vec::new(),
             transactions: Vec::new(),
+            budgets: Vec::new(),
+            recurrences: Vec::new(),
         }
     }
 }
@@ -83,6 +93,319 @@ impl Company {
         }
     }
 
+    pub fn rename_account(&mut self, account_id: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        // names must be usable in account paths and unique among siblings
+        let name = name.trim();
+        let account = self.get_acccount_by_id(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+
+        if name.is_empty() || name.contains(':') {
+            return Err("account names cannot be empty or contain a colon".into());
+        }
+
+        let taken = self.get_children(&account.parent)
+            .iter()
+            .any(|sibling| sibling.id != account_id && sibling.name.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(format!("another account is already called {}", name).into());
+        }
+
+        if let Some(account) = self.get_acccount_by_id_mut(account_id) {
+            account.set_name(name);
+        }
+
+        Ok(())
+    }
+
+    pub fn move_account(&mut self, account_id: &str, parent_id: &str) -> Result<(), Box<dyn Error>> {
+        // re-parents the account keeping parent and subaccounts in step.
+        // the account has to stay on the same debit or credit side
+        let account = self.get_acccount_by_id(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+        let old_parent = account.parent.clone();
+
+        if parent_id != "0" {
+            let parent = self.get_acccount_by_id(parent_id)
+                .ok_or_else(|| format!("Account_id {} cannot be found", parent_id))?;
+
+            if self.get_descendant_ids(account_id).iter().any(|id| id == parent_id) {
+                return Err("an account cannot be moved under itself".into());
+            }
+            if parent.r#type != account.r#type {
+                return Err(format!("{} and {} are on different sides of the ledger", account.name, parent.name).into());
+            }
+        }
+
+        let taken = self.get_children(parent_id)
+            .iter()
+            .any(|sibling| sibling.id != account_id && sibling.name.eq_ignore_ascii_case(&account.name));
+        if taken {
+            return Err(format!("another account is already called {} there", account.name).into());
+        }
+
+        if let Some(parent) = self.get_acccount_by_id_mut(&old_parent) {
+            parent.remove_subaccount(account_id);
+        }
+        if let Some(parent) = self.get_acccount_by_id_mut(parent_id) {
+            parent.add_subaccount(account_id);
+        }
+        if let Some(account) = self.get_acccount_by_id_mut(account_id) {
+            account.set_parent(parent_id);
+        }
+
+        Ok(())
+    }
+
+    pub fn merge_account(&mut self, from_id: &str, into_id: &str) -> Result<(), Box<dyn Error>> {
+        // moves everything recorded against one account onto another
+        // and then removes the emptied account
+        let from = self.get_acccount_by_id(from_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", from_id))?;
+        let into = self.get_acccount_by_id(into_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", into_id))?;
+
+        if from_id == into_id {
+            return Err("an account cannot be merged into itself".into());
+        }
+        if self.get_descendant_ids(from_id).iter().any(|id| id == into_id) {
+            return Err("an account cannot be merged into its own subaccount".into());
+        }
+        if from.r#type != into.r#type {
+            return Err(format!("{} and {} are on different sides of the ledger", from.name, into.name).into());
+        }
+        // a transfer between the two would be left posting to one account
+        let between = |debit: &str, credit: &str| {
+            (debit == from_id && credit == into_id) || (debit == into_id && credit == from_id)
+        };
+        if let Some(transaction) = self.transactions.iter().find(|transaction| between(&transaction.debit, &transaction.credit)) {
+            return Err(format!("transaction_id {} moves money between {} and {}", transaction.id, from.name, into.name).into());
+        }
+        if let Some(recurrence) = self.recurrences.iter().find(|recurrence| between(&recurrence.debit, &recurrence.credit)) {
+            return Err(format!("scheduled transaction {} moves money between {} and {}", recurrence.id, from.name, into.name).into());
+        }
+        // the subaccounts move under the target, so every name has to be
+        // free there before anything changes
+        let into_children = self.get_children(into_id);
+        for child in self.get_children(from_id) {
+            if into_children.iter().any(|sibling| sibling.name.eq_ignore_ascii_case(&child.name)) {
+                return Err(format!("{} already has an account called {}", into.name, child.name).into());
+            }
+        }
+
+        let transaction_ids = from.transactions.clone();
+        let children = self.get_children(from_id)
+            .iter()
+            .map(|child| child.id.clone())
+            .collect::<Vec<String>>();
+
+        for transaction in self.transactions.iter_mut() {
+            if transaction.debit == from_id {
+                transaction.set_debit(into_id);
+            }
+            if transaction.credit == from_id {
+                transaction.set_credit(into_id);
+            }
+        }
+        for recurrence in self.recurrences.iter_mut() {
+            if recurrence.debit == from_id {
+                recurrence.debit = into_id.to_string();
+            }
+            if recurrence.credit == from_id {
+                recurrence.credit = into_id.to_string();
+            }
+        }
+        if let Some(into) = self.get_acccount_by_id_mut(into_id) {
+            for id in &transaction_ids {
+                into.add_transaction(id);
+            }
+        }
+
+        for child in children {
+            self.move_account(&child, into_id)?;
+        }
+
+        // the budget moves across unless the target already has its own
+        if self.get_budget_by_account(into_id).is_some() {
+            self.budgets.retain(|budget| budget.account != from_id);
+        }
+        for budget in self.budgets.iter_mut() {
+            if budget.account == from_id {
+                budget.set_account(into_id);
+            }
+        }
+
+        if let Some(account) = self.get_acccount_by_id_mut(from_id) {
+            account.transactions.clear();
+        }
+        self.remove_account(from_id)
+    }
+
+    pub fn close_account(&mut self, account_id: &str) -> Result<(), Box<dyn Error>> {
+        // closes the account and everything below it
+        let account = self.get_acccount_by_id(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+
+        if account.closed {
+            return Err(format!("{} is already closed", account.name).into());
+        }
+
+        for id in self.get_descendant_ids(account_id) {
+            if let Some(account) = self.get_acccount_by_id_mut(&id) {
+                account.closed = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn reopen_account(&mut self, account_id: &str) -> Result<(), Box<dyn Error>> {
+        // reopens the account along with the parents it needs to be reachable
+        let account = self.get_acccount_by_id(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+
+        if !account.closed {
+            return Err(format!("{} is not closed", account.name).into());
+        }
+
+        let mut current = account_id.to_string();
+        while let Some(account) = self.get_acccount_by_id_mut(&current) {
+            if !account.closed {
+                break;
+            }
+            account.closed = false;
+            current = account.parent.clone();
+        }
+
+        Ok(())
+    }
+
+    pub fn create_account(&mut self, parent_id: &str, name: &str, r#type: &str) -> Result<String, Box<dyn Error>> {
+        // adds a new account under the parent and returns its id.
+        // subaccounts always take the type of their parent
+        let name = name.trim();
+
+        if name.is_empty() || name.contains(':') {
+            return Err("account names cannot be empty or contain a colon".into());
+        }
+
+        let r#type = if parent_id == "0" {
+            r#type.to_string()
+        }
+        else {
+            let parent = self.get_acccount_by_id(parent_id)
+                .ok_or_else(|| format!("Account_id {} cannot be found", parent_id))?;
+            parent.r#type.clone()
+        };
+        if !matches!(&r#type[..], "d" | "c") {
+            return Err("account type has to be d or c".into());
+        }
+
+        let taken = self.get_children(parent_id)
+            .iter()
+            .any(|sibling| sibling.name.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(format!("another account is already called {} there", name).into());
+        }
+
+        let mut account = Account::new();
+        account.id = (self.max_id_account().unwrap_or(0) + 1).to_string();
+        account.set_name(name).set_parent(parent_id);
+        account.r#type = r#type;
+        let id = account.id.clone();
+
+        if let Some(parent) = self.get_acccount_by_id_mut(parent_id) {
+            parent.add_subaccount(&id);
+        }
+        self.insert_account(account);
+
+        Ok(id)
+    }
+
+    pub fn set_account_cash(&mut self, account_id: &str, cash: bool) -> Result<(), Box<dyn Error>> {
+        let account = self.get_acccount_by_id_mut(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+
+        if account.r#type != "d" && cash {
+            return Err(format!("{} is a credit account and cannot hold cash", account.name).into());
+        }
+        account.cash = cash;
+
+        Ok(())
+    }
+
+    pub fn set_account_category(&mut self, account_id: &str, category: &str) -> Result<(), Box<dyn Error>> {
+        // an empty category goes back to following the parent
+        if !category.is_empty() && !cashflow::CATEGORIES.contains(&category) {
+            return Err(format!("the category has to be one of {}", cashflow::CATEGORIES.join(", ")).into());
+        }
+
+        let account = self.get_acccount_by_id_mut(account_id)
+            .ok_or_else(|| format!("Account_id {} cannot be found", account_id))?;
+        account.category = category.to_string();
+
+        Ok(())
+    }
+
+    pub fn get_cash_account_ids(&self) -> Vec<String> {
+        // accounts marked as cash along with their subaccounts
+        let mut ids = Vec::<String>::new();
+
+        for account in self.accounts.iter().filter(|account| account.cash) {
+            for id in self.get_descendant_ids(&account.id) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids
+    }
+
+    pub fn get_account_category(&self, account_id: &str) -> String {
+        // the nearest category set on the account or its parents
+        let mut current = self.get_acccount_by_id(account_id);
+        let mut steps = 0;
+
+        while let Some(account) = current {
+            if !account.category.is_empty() {
+                return account.category.clone();
+            }
+            steps += 1;
+            if steps > self.accounts.len() {
+                break;
+            }
+            current = self.get_acccount_by_id(&account.parent);
+        }
+
+        "operating".to_string()
+    }
+
+    pub fn remove_account(&mut self, to_delete: &str) -> Result<(), Box<dyn Error>> {
+        // deletes the account only when nothing else refers to it
+        let account = self.get_acccount_by_id(to_delete)
+            .ok_or_else(|| format!("Account_id {} cannot be found", to_delete))?;
+
+        if !self.get_children(to_delete).is_empty() {
+            return Err(format!("{} still has subaccounts", account.name).into());
+        }
+
+        let in_use = self.transactions
+            .iter()
+            .any(|transaction| transaction.debit == to_delete || transaction.credit == to_delete);
+        if in_use {
+            return Err(format!("{} still has transactions. Merge or close it instead", account.name).into());
+        }
+
+        let parent = account.parent.clone();
+        if let Some(parent) = self.get_acccount_by_id_mut(&parent) {
+            parent.remove_subaccount(to_delete);
+        }
+        self.budgets.retain(|budget| budget.account != to_delete);
+        self.delete_account(to_delete);
+
+        Ok(())
+    }
+
     pub fn insert_transaction(&mut self, transaction: Transaction) {
         // will not insert transaction with non-unique id
 
@@ -116,6 +439,181 @@ impl Company {
         }
     }
 
+    pub fn post_transaction(&mut self, mut transaction: Transaction) -> String {
+        // gives the transaction the next id, inserts it and records it
+        // against both the debit and credit accounts
+        let current_max = self.max_id_transaction().unwrap_or(0);
+        transaction.id = (current_max + 1).to_string();
+        let id = transaction.id.clone();
+
+        for account_id in [transaction.debit.clone(), transaction.credit.clone()] {
+            if let Some(account) = self.get_acccount_by_id_mut(&account_id) {
+                account.add_transaction(&id);
+            }
+        }
+        self.insert_transaction(transaction);
+
+        id
+    }
+
+    pub fn remove_transaction(&mut self, to_delete: &str, confirmed: bool) -> Result<(), Box<dyn Error>> {
+        // deletes the transaction and unlinks it from its accounts.
+        // reconciled transactions need the caller to confirm first
+        let transaction = match self.get_transaction_by_id(to_delete) {
+            Some(transaction) => transaction,
+            None => return Err(format!("Transaction_id {} cannot be found", to_delete).into()),
+        };
+
+        if transaction.is_reconciled() && !confirmed {
+            return Err(format!("Transaction_id {} has been reconciled with the bank. Confirm to delete it anyway", to_delete).into());
+        }
+
+        for account in self.accounts.iter_mut() {
+            account.remove_transaction(to_delete);
+        }
+        self.delete_transaction(to_delete);
+
+        Ok(())
+    }
+
+    pub fn update_transaction(&mut self, mut transaction: Transaction, confirmed: bool) -> Result<(), Box<dyn Error>> {
+        // replaces the transaction with the same id.
+        // reconciled transactions need the caller to confirm first
+        let existing = match self.get_transaction_by_id(&transaction.id) {
+            Some(existing) => existing,
+            None => return Err(format!("Transaction_id {} cannot be found", transaction.id).into()),
+        };
+
+        if existing.is_reconciled() && !confirmed {
+            return Err(format!("Transaction_id {} has been reconciled with the bank. Confirm to edit it anyway", transaction.id).into());
+        }
+        if transaction.debit == transaction.credit {
+            return Err("the debit and credit are the same account".into());
+        }
+
+        // the bank has not seen a posting moved to another account
+        if existing.debit != transaction.debit {
+            transaction.debit_status = "uncleared".to_string();
+        }
+        if existing.credit != transaction.credit {
+            transaction.credit_status = "uncleared".to_string();
+        }
+
+        let id = transaction.id.clone();
+        for account in self.accounts.iter_mut() {
+            if account.id == transaction.debit || account.id == transaction.credit {
+                account.add_transaction(&id);
+            }
+            else {
+                account.remove_transaction(&id);
+            }
+        }
+        self.delete_transaction(&id);
+        self.insert_transaction(transaction);
+
+        Ok(())
+    }
+
+    pub fn get_transaction_by_id(&self, id: &str) -> Option<&Transaction> {
+        self.transactions.iter().find(|transaction| transaction.id == id)
+    }
+
+    pub fn insert_recurrence(&mut self, recurrence: Recurrence) {
+        // will not insert recurrence with non-unique id
+
+        if self.get_recurrence_by_id(&recurrence.id).is_none() {
+            self.recurrences.push(recurrence);
+        }
+    }
+
+    pub fn delete_recurrence(&mut self, to_delete: &str) {
+        self.recurrences.retain(|recurrence| recurrence.id != to_delete);
+    }
+
+    pub fn get_recurrence_by_id(&self, id: &str) -> Option<&Recurrence> {
+        self.recurrences.iter().find(|recurrence| recurrence.id == id)
+    }
+
+    pub fn get_due_recurrences(&self, today: NaiveDate) -> Vec<(&Recurrence, NaiveDate)> {
+        // every occurrence on or before today that has not been generated
+        let mut due = Vec::new();
+
+        for recurrence in &self.recurrences {
+            for date in recurrence.get_dates_until(today) {
+                due.push((recurrence, date));
+            }
+        }
+        due.sort_by_key(|(_, date)| *date);
+
+        due
+    }
+
+    pub fn get_upcoming_recurrences(&self, today: NaiveDate, until: NaiveDate) -> Vec<(&Recurrence, NaiveDate)> {
+        // occurrences after today up to and including until
+        let mut upcoming = Vec::new();
+
+        for recurrence in &self.recurrences {
+            for date in recurrence.get_dates_until(until) {
+                if date > today {
+                    upcoming.push((recurrence, date));
+                }
+            }
+        }
+        upcoming.sort_by_key(|(_, date)| *date);
+
+        upcoming
+    }
+
+    pub fn post_due_recurrences(&mut self, today: NaiveDate) -> Result<Vec<String>, Box<dyn Error>> {
+        // generates the transactions for every due occurrence and
+        // returns the ids of the transactions created. nothing is posted
+        // while a due recurrence names a closed or missing account
+        for recurrence in &self.recurrences {
+            if recurrence.get_next_date().map_or(true, |date| date > today) {
+                continue;
+            }
+            for id in [&recurrence.debit, &recurrence.credit] {
+                match self.get_acccount_by_id(id) {
+                    Some(account) if account.closed => {
+                        return Err(format!("scheduled transaction {} posts to {}, which is closed", recurrence.id, self.get_account_path(id)).into());
+                    },
+                    Some(_) => {},
+                    None => return Err(format!("scheduled transaction {} posts to account_id {}, which does not exist", recurrence.id, id).into()),
+                }
+            }
+        }
+
+        let mut transactions = Vec::new();
+
+        for recurrence in self.recurrences.iter_mut() {
+            for date in recurrence.get_dates_until(today) {
+                transactions.push(recurrence.make_transaction(date));
+                recurrence.occurrences += 1;
+            }
+        }
+        transactions.sort_by_key(|transaction| transaction.date);
+
+        Ok(transactions
+            .into_iter()
+            .map(|transaction| self.post_transaction(transaction))
+            .collect())
+    }
+
+    pub fn insert_budget(&mut self, budget: Budget) {
+        // an account can only have one budget, so a new one replaces the old
+
+        self.budgets.retain(|element| element.account != budget.account);
+        self.budgets.push(budget);
+    }
+
+    pub fn delete_budget(&mut self, to_delete: &str) {
+        self.budgets.retain(|budget| budget.id != to_delete);
+    }
+
+    pub fn get_budget_by_account(&self, account_id: &str) -> Option<&Budget> {
+        self.budgets.iter().find(|budget| budget.account == account_id)
+    }
+
     pub fn sort_accounts(&mut self, direction: &str) {
         // defaults to ascneding sort in case of argument mistype
         match direction {
@@ -137,14 +635,40 @@ impl Company {
     pub fn max_id_account(&mut self) -> Result<usize, Box<dyn Error>> {
         // find the maximum id in the accounts
         self.sort_accounts("desc");
-        let max_id = self.accounts[0].id.parse::<usize>()?;
+        let max_id = match self.accounts.first() {
+            Some(account) => account.id.parse::<usize>()?,
+            None => 0,
+        };
         Ok(max_id)
     }
 
     pub fn max_id_transaction(&mut self) -> Result<usize, Box<dyn Error>> {
         // find the maximum id in the transactions
         self.sort_transactions("desc");
-        let max_id = self.transactions[0].id.parse::<usize>()?;
+        let max_id = match self.transactions.first() {
+            Some(transaction) => transaction.id.parse::<usize>()?,
+            None => 0,
+        };
+        Ok(max_id)
+    }
+
+    pub fn max_id_recurrence(&mut self) -> Result<usize, Box<dyn Error>> {
+        // find the maximum id in the recurrences
+        self.recurrences.sort_by(|a, b| b.cmp_id(a));
+        let max_id = match self.recurrences.first() {
+            Some(recurrence) => recurrence.id.parse::<usize>()?,
+            None => 0,
+        };
+        Ok(max_id)
+    }
+
+    pub fn max_id_budget(&mut self) -> Result<usize, Box<dyn Error>> {
+        // find the maximum id in the budgets
+        self.budgets.sort_by(|a, b| b.cmp_id(a));
+        let max_id = match self.budgets.first() {
+            Some(budget) => budget.id.parse::<usize>()?,
+            None => 0,
+        };
         Ok(max_id)
     }
 
@@ -167,6 +691,61 @@ impl Company {
         }
     }
 
+    pub fn get_acccount_by_id_mut(&mut self, id: &str) -> Option<&mut Account> {
+        self.accounts.iter_mut().find(|account| account.id == id)
+    }
+
+    pub fn get_account_path(&self, account_id: &str) -> String {
+        // the names from the root down to the account, such as Expenses:Food
+        let mut names = Vec::<&str>::new();
+        let mut current = self.get_acccount_by_id(account_id);
+
+        while let Some(account) = current {
+            if names.len() > self.accounts.len() {
+                // a broken parent chain must not loop forever
+                break;
+            }
+            names.push(&account.name);
+            current = self.get_acccount_by_id(&account.parent);
+        }
+        names.reverse();
+
+        names.join(":")
+    }
+
+    pub fn get_account_by_path(&self, path: &str) -> Option<&Account> {
+        // only a full path, such as Expenses:Food
+        self.accounts
+            .iter()
+            .find(|account| self.get_account_path(&account.id).eq_ignore_ascii_case(path))
+    }
+
+    pub fn find_account_by_path(&self, path: &str) -> Option<&Account> {
+        // accepts a full path, a unique account name or an id
+        if let Some(account) = self.get_account_by_path(path) {
+            return Some(account);
+        }
+
+        let named = self.accounts
+            .iter()
+            .filter(|account| account.name.eq_ignore_ascii_case(path))
+            .collect::<Vec<&Account>>();
+        if named.len() == 1 {
+            return Some(named[0]);
+        }
+
+        self.get_acccount_by_id(path)
+    }
+
+    pub fn get_open_account_id(&self, name: &str) -> Result<String, Box<dyn Error>> {
+        // the account a new transaction can be posted to, by path, name or id
+        match self.find_account_by_path(name) {
+            Some(account) if account.closed => Err(format!("{} is closed", name).into()),
+            Some(account) => Ok(account.id.clone()),
+            None => Err(format!("no account matches {}", name).into()),
+        }
+    }
+
     pub fn get_transactions_by_account(&self, account_id: &str) -> Option<Vec<&Transaction>> {
     
         let option = self.get_acccount_by_id(account_id);
@@ -208,6 +787,11 @@ impl Company {
         Some(accounts)
     }
 
+    pub fn get_open_accounts(&self) -> Vec<&Account> {
+        // the accounts that can still be picked for new entries
+        self.accounts.iter().filter(|account| !account.closed).collect()
+    }
+
     pub fn get_chart_of_accounts(&self) -> Option<Vec<Vec<&Account>>> {
         let accounts = self.get_accounts();
 
@@ -239,48 +823,6 @@ impl Company {
         Some(chart)
     }
 
-    pub fn generate_expense_report<T>(&self, db_path: T) -> Result<Option<PathBuf>, Box<dyn Error>>
-        where T: AsRef<Path>
-    {
-        // this function communicates an external service to generate
-        // an expense resport based on the database JSON file.
-        // the networking library used is ZMQ
-        // the microservice returns the path to the created report
-        let ctx = zmq::Context::new();
-        let socket = ctx.socket(zmq::REQ)?;
-        socket.connect("tcp://127.0.0.1:6000")?;
-
-        let payload = file_io::read(db_path)?;
-        socket.send(&payload, 0)?;
-
-        let mut msg = zmq::Message::new();
-        socket.recv(&mut msg, 0)?;
-
-        let path = msg.as_str();
-
-        if path.is_some() {
-            let path = path.unwrap();
-            let report_location = PathBuf::from(format!("{}", path));
-            let mut result = report_location.clone();
-
-            // convert service string in an appropriate path regardless of OS
-            let filename = report_location.file_name();
-            if filename.is_some() {
-                let filename = filename.unwrap().to_str().unwrap();
-                let mut split = filename.split("\\");
-                result.pop();
-                for name in split {
-                    result.push(name);
-                }
-            }
-            return Ok(Some(result))
-        }
-        else {
-            return Ok(None);
-        }
-
-    }
-
     pub fn get_balance_summary(&self) -> Option<Vec<(&Account, f64)>> {
         let accounts = self.get_accounts();
 
@@ -303,6 +845,12 @@ impl Company {
     }
 
     fn get_account_balance(&self, account_id: &String) -> f64 {
+        self.get_account_balance_between(account_id, None, None)
+    }
+
+    pub fn get_account_balance_between(&self, account_id: &str, start: Option<NaiveDate>, end: Option<NaiveDate>) -> f64 {
+        // balance of the account counting only transactions dated
+        // within the inclusive bounds. None leaves that side open
         let transactions = self.get_transactions_by_account(account_id);
 
         if transactions.is_none() {
@@ -317,6 +865,11 @@ impl Company {
         let mut sum = 0.0;
 
         for transaction in transactions.iter() {
+            if start.is_some_and(|start| transaction.date < start)
+                || end.is_some_and(|end| transaction.date > end) {
+                continue;
+            }
+
             let amount = transaction.amount.parse::<f64>().unwrap();
 
             if is_debit && transaction.debit == account.id {
@@ -336,4 +889,266 @@ impl Company {
         sum
     }
 
+    pub fn get_account_tree(&self) -> Vec<(&Account, usize)> {
+        // walk the accounts depth first from the root (id = 0)
+        // and pair each account with its depth in the tree
+        let mut tree = Vec::<(&Account, usize)>::with_capacity(self.accounts.len());
+        let mut stack = self.get_children("0")
+            .into_iter()
+            .rev()
+            .map(|account| (account, 0))
+            .collect::<Vec<(&Account, usize)>>();
+
+        while let Some((account, depth)) = stack.pop() {
+            if tree.iter().any(|(seen, _)| seen.id == account.id) {
+                // a broken parent chain must not loop forever
+                continue;
+            }
+            tree.push((account, depth));
+
+            for child in self.get_children(&account.id).into_iter().rev() {
+                stack.push((child, depth + 1));
+            }
+        }
+
+        tree
+    }
+
+    pub fn get_children(&self, account_id: &str) -> Vec<&Account> {
+        // children are found by their parent field, ordered by id
+        let mut children = self.accounts
+            .iter()
+            .filter(|account| account.parent == account_id && account.id != account_id)
+            .collect::<Vec<&Account>>();
+        children.sort_by(|a, b| a.cmp(b));
+
+        children
+    }
+
+    pub fn get_descendant_ids(&self, account_id: &str) -> Vec<String> {
+        // the account itself followed by every account below it
+        let mut ids = vec![account_id.to_string()];
+        let mut idx = 0;
+
+        while idx < ids.len() {
+            for child in self.get_children(&ids[idx]) {
+                if !ids.contains(&child.id) {
+                    ids.push(child.id.clone());
+                }
+            }
+            idx += 1;
+        }
+
+        ids
+    }
+
+    pub fn get_rollup_balance_between(&self, account_id: &str, start: Option<NaiveDate>, end: Option<NaiveDate>) -> f64 {
+        // balance of the account including all of its subaccounts
+        self.get_descendant_ids(account_id)
+            .iter()
+            .map(|id| self.get_account_balance_between(id, start, end))
+            .sum()
+    }
+
+    pub fn get_latest_transaction_date(&self) -> Option<NaiveDate> {
+        self.transactions.iter().map(|transaction| transaction.date).max()
+    }
+
+    pub fn get_current_period(&self, period: &str) -> (NaiveDate, NaiveDate) {
+        // the period holding the most recent activity, or today without any
+        let date = self
+            .get_latest_transaction_date()
+            .unwrap_or_else(|| Local::today().naive_local());
+
+        budget::period_bounds(period, date)
+    }
+
+    pub fn get_budget_report(&self, period: &str, date: NaiveDate) -> Option<Vec<BudgetLine<'_>>> {
+        // compares actual spending against budgets for the period containing date.
+        // budgets and actuals both roll up through the account tree,
+        // and only branches that carry a budget somewhere are reported
+        if self.budgets.is_empty() {
+            return None;
+        }
+
+        let (start, end) = budget::period_bounds(period, date);
+
+        let lines = self.get_account_tree()
+            .into_iter()
+            .filter_map(|(account, depth)| {
+                let ids = self.get_descendant_ids(&account.id);
+                let budgets = self.budgets
+                    .iter()
+                    .filter(|budget| ids.contains(&budget.account))
+                    .collect::<Vec<&Budget>>();
+
+                if budgets.is_empty() {
+                    return None;
+                }
+
+                Some(BudgetLine {
+                    account,
+                    depth,
+                    actual: self.get_rollup_balance_between(&account.id, Some(start), Some(end)),
+                    budget: budgets.iter().map(|budget| budget.get_amount_for_period(period)).sum(),
+                })
+            })
+            .collect();
+
+        Some(lines)
+    }
+
+    pub fn filtered(&self, filter: &Filter) -> Cow<'_, Company> {
+        // the company holding only the matching transactions so any report
+        // can be run against it unchanged. without a filter it is borrowed,
+        // otherwise only the matching transactions are copied
+        if filter.is_empty() {
+            return Cow::Borrowed(self);
+        }
+
+        Cow::Owned(Company {
+            accounts: self.accounts.clone(),
+            transactions: self.transactions
+                .iter()
+                .filter(|transaction| filter.matches(transaction))
+                .cloned()
+                .collect(),
+            budgets: self.budgets.clone(),
+            recurrences: self.recurrences.clone(),
+        })
+    }
+
+    pub fn group_transactions<F>(&self, keys: F) -> Vec<(String, f64, usize)>
+    where F: Fn(&Transaction) -> Vec<String>
+    {
+        // totals the transactions under every key they produce,
+        // giving (key, total amount, number of transactions) sorted by key
+        let mut groups = Vec::<(String, f64, usize)>::new();
+
+        for transaction in &self.transactions {
+            let amount = transaction.amount.parse::<f64>().unwrap_or(0.0);
+
+            for key in keys(transaction) {
+                match groups.iter_mut().find(|group| group.0 == key) {
+                    Some(group) => {
+                        group.1 += amount;
+                        group.2 += 1;
+                    },
+                    None => groups.push((key, amount, 1)),
+                }
+            }
+        }
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+        groups
+    }
+
+    pub fn group_by_payee(&self) -> Vec<(String, f64, usize)> {
+        self.group_transactions(|transaction| {
+            if transaction.payee.is_empty() {
+                vec!["(no payee)".to_string()]
+            }
+            else {
+                vec![transaction.payee.clone()]
+            }
+        })
+    }
+
+    pub fn group_by_tag(&self) -> Vec<(String, f64, usize)> {
+        self.group_transactions(|transaction| {
+            transaction.tags.iter().map(|tag| format!("#{}", tag)).collect()
+        })
+    }
+
+    pub fn group_by_metadata(&self, key: &str) -> Vec<(String, f64, usize)> {
+        self.group_transactions(|transaction| {
+            match transaction.metadata.get(key) {
+                Some(value) => vec![value.clone()],
+                None => Vec::new(),
+            }
+        })
+    }
+
+    pub fn get_metadata_keys(&self) -> Vec<String> {
+        let mut keys = Vec::<String>::new();
+
+        for transaction in &self.transactions {
+            for key in transaction.metadata.keys() {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        keys.sort();
+
+        keys
+    }
+
+    pub fn get_transaction_dates(&self) -> Vec<NaiveDate> {
+        // every distinct transaction date in order
+        let mut dates = self.transactions
+            .iter()
+            .map(|transaction| transaction.date)
+            .collect::<Vec<NaiveDate>>();
+        dates.sort();
+        dates.dedup();
+
+        dates
+    }
+
+    pub fn get_balance_history(&self, account_id: &str) -> Vec<(NaiveDate, f64)> {
+        // the balance of the account and its subaccounts at the end of each
+        // day that has transactions
+        self.get_transaction_dates()
+            .into_iter()
+            .map(|date| (date, self.get_rollup_balance_between(account_id, None, Some(date))))
+            .collect()
+    }
+
+    pub fn get_net_worth_history(&self, assets: &str, liabilities: &str) -> Vec<(NaiveDate, f64)> {
+        // assets less liabilities at the end of each day that has transactions,
+        // given the paths of the two accounts
+        let assets = self.find_account_by_path(assets).map(|account| account.id.clone());
+        let liabilities = self.find_account_by_path(liabilities).map(|account| account.id.clone());
+
+        self.get_transaction_dates()
+            .into_iter()
+            .map(|date| {
+                let balance = |id: &Option<String>| match id {
+                    Some(id) => self.get_rollup_balance_between(id, None, Some(date)),
+                    None => 0.0,
+                };
+                (date, balance(&assets) - balance(&liabilities))
+            })
+            .collect()
+    }
+
+    pub fn get_monthly_income_expense(&self, income: &str, expenses: &str) -> Vec<(NaiveDate, f64, f64)> {
+        // income and expenses for every month from the first transaction to the last,
+        // keyed by the first day of the month, given the paths of the two accounts
+        let income = self.find_account_by_path(income).map(|account| account.id.clone());
+        let expenses = self.find_account_by_path(expenses).map(|account| account.id.clone());
+
+        let dates = self.get_transaction_dates();
+        let (first, last) = match (dates.first(), dates.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Vec::new(),
+        };
+
+        let mut months = Vec::new();
+        let mut month = NaiveDate::from_ymd(first.year(), first.month(), 1);
+
+        while month <= last {
+            let (start, end) = budget::period_bounds("month", month);
+            let total = |id: &Option<String>| match id {
+                Some(id) => self.get_rollup_balance_between(id, Some(start), Some(end)),
+                None => 0.0,
+            };
+            months.push((month, total(&income), total(&expenses)));
+            month = end.succ();
+        }
+
+        months
+    }
+
 }
@@ -1,35 +1,500 @@
-use clap::Parser;
+use chrono::prelude::*;
+use clap::{Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
 use std::time::Duration;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::file_io;
+use crate::service::{ServiceRegistry, ServiceSettings};
+use crate::theme::ThemeEntry;
+use crate::state;
 
 #[derive(Debug, Parser)]
+#[clap(disable_help_subcommand = true)]
 pub struct Args {
+    #[clap(short = 'c',
+        long,
+        help = "Read settings from this file instead of config.toml in the user config directory")]
+    pub config: Option<String>,
+
     #[clap(short = 'd', 
         long,
-        default_value = "data/db.json",
-        help = "Specify path to database to load")]
-    pub database: String,
+        help = "Specify path to database to load. Defaults to data/db.json")]
+    pub database: Option<String>,
     
     #[clap(short = 't', 
         long, 
-        default_value = "200",
-        help = "Determines animation speeds in ms.  Default is fine in most cases")]
-    pub tick_rate: u64
+        help = "Determines animation speeds in ms.  Default of 200 is fine in most cases")]
+    pub tick_rate: Option<u64>,
+
+    #[clap(long, help = "How dates are shown, such as %d/%m/%Y. Defaults to %Y-%m-%d")]
+    pub date_format: Option<String>,
+
+    #[clap(long, help = "Symbol shown before amounts, such as $")]
+    pub currency: Option<String>,
+
+    #[clap(long, help = "Name of the color theme, such as dark, light, high-contrast or monochrome")]
+    pub theme: Option<String>,
+
+    #[clap(long, help = "Turn off blinking, italics and other effects")]
+    pub no_effects: bool,
+
+    #[clap(long = "service",
+        help = "Register a service as name=<name>,endpoint=<address> with optional transport, timeout, retries and actions. Repeat for more")]
+    pub services: Vec<String>,
+
+    #[clap(long,
+        help = "Address of the report service. Defaults to tcp://127.0.0.1:6000")]
+    pub service_endpoint: Option<String>,
+
+    #[clap(long,
+        help = "How long in ms to wait for each reply from the report service. Defaults to 5000")]
+    pub service_timeout: Option<u64>,
+
+    #[clap(long,
+        help = "How many times to retry the report service after a timeout. Defaults to 2")]
+    pub service_retries: Option<usize>,
+
+    #[clap(long,
+        help = "Send the bare database to a report service that predates the JSON protocol")]
+    pub service_legacy: bool,
+
+    #[clap(long,
+        help = "Publish every change to the ledger on this ZMQ address, such as tcp://127.0.0.1:6002")]
+    pub publish: Option<String>,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Commands run against the database without opening the interface
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[clap(about = "List the transactions matching a query, such as account:Expenses:Food and amount>50")]
+    Query {
+        #[clap(help = "Terms joined by and. Fields are account, amount, date, memo, payee, tag, meta, sort and limit")]
+        query: Vec<String>,
+
+        #[clap(short = 'b',
+            long,
+            help = "Show account balances over the matching transactions instead")]
+        balance: bool,
+    },
+
+    #[clap(about = "Print the cash flow statement for a period")]
+    CashFlow {
+        #[clap(short = 's', long, help = "First day of the statement as YYYY-MM-DD")]
+        start: Option<String>,
+
+        #[clap(short = 'e', long, help = "Last day of the statement as YYYY-MM-DD")]
+        end: Option<String>,
+
+        #[clap(short = 'p',
+            long,
+            default_value = "month",
+            help = "month, quarter or year holding the latest transaction, used when no dates are given")]
+        period: String,
+    },
+
+    #[clap(about = "List the registered services and the actions that use them")]
+    Services,
+
+    #[clap(about = "Read the manual. Lists the topics without one")]
+    Help {
+        #[clap(help = "A topic, or words to search the manual for")]
+        topic: Vec<String>,
+    },
+
+    #[clap(about = "Learn the basics on a scratch copy of a sample ledger, leaving your own untouched")]
+    Tutorial,
+
+    #[clap(about = "Work with the settings file")]
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+
+    #[clap(about = "Answer ledger requests over ZMQ until stopped")]
+    Serve {
+        #[clap(short = 'e',
+            long,
+            default_value = "tcp://127.0.0.1:6001",
+            help = "Address to bind the REP socket to")]
+        endpoint: String,
+
+        #[clap(short = 'p', long, help = "Also publish each change on this address. Defaults to --publish")]
+        publish: Option<String>,
+    },
+
+    #[clap(about = "Total the Expenses subaccounts for a period, printed or exported")]
+    Expenses {
+        #[clap(short = 's', long, help = "First day of the report as YYYY-MM-DD")]
+        start: Option<String>,
+
+        #[clap(short = 'e', long, help = "Last day of the report as YYYY-MM-DD")]
+        end: Option<String>,
+
+        #[clap(short = 'p',
+            long,
+            default_value = "month",
+            help = "month, quarter or year holding the latest transaction, used when no dates are given")]
+        period: String,
+
+        #[clap(short = 'f', long, help = "Export as csv, html or md instead of printing")]
+        format: Option<String>,
+
+        #[clap(short = 'o', long, help = "Where to write the export. Defaults to the database directory")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    #[clap(about = "Print the settings in effect after the file, environment and flags are combined")]
+    Show,
+}
+
+// The settings file. every entry is optional so a file only
+// needs to hold what it changes
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub database: Option<String>,
+    pub state: Option<String>,
+    // where Money remembers what the user has seen
+    pub tick_rate: Option<u64>,
+    // in ms
+    pub date_format: Option<String>,
+    pub currency: Option<String>,
+    pub theme: Option<String>,
+    pub effects: Option<bool>,
+    pub publish: Option<String>,
+    pub assets_account: Option<String>,
+    pub liabilities_account: Option<String>,
+    // the accounts net worth is worked out from, by path
+    pub income_account: Option<String>,
+    pub expenses_account: Option<String>,
+    // the accounts charted as income and expenses by month, by path
+    #[serde(default)]
+    pub keymap: BTreeMap<String, String>,
+    // action name to key, such as quit = "q"
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeEntry>,
+    #[serde(default)]
+    pub services: Vec<ServiceEntry>,
+}
+
+// One [[services]] table in the settings file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceEntry {
+    pub name: String,
+    pub endpoint: String,
+    pub transport: Option<String>,
+    pub timeout: Option<u64>,
+    // in ms
+    pub retries: Option<usize>,
+    pub actions: Option<Vec<String>>,
+}
+
+impl ServiceEntry {
+
+    pub fn to_settings(&self) -> Result<ServiceSettings, Box<dyn Error>> {
+        // anything left out takes the defaults of the report service
+        let mut service = ServiceSettings {
+            name: self.name.clone(),
+            endpoint: self.endpoint.clone(),
+            actions: self.actions.clone().unwrap_or_default(),
+            ..ServiceSettings::default()
+        };
+        if let Some(transport) = &self.transport {
+            service.set_transport(transport)?;
+        }
+        if let Some(timeout) = self.timeout {
+            service.timeout = Duration::from_millis(timeout);
+        }
+        if let Some(retries) = self.retries {
+            service.retries = retries;
+        }
+
+        Ok(service)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database: PathBuf,
+    pub state: Option<PathBuf>,
     pub tick_rate: Duration,
+    pub services: ServiceRegistry,
+    pub publish: Option<String>,
+    pub date_format: String,
+    pub currency: String,
+    pub theme: String,
+    pub effects: bool,
+    // blinking and italics, off for readers who find them hard to follow
+    pub assets_account: String,
+    pub liabilities_account: String,
+    pub income_account: String,
+    pub expenses_account: String,
+    pub themes: BTreeMap<String, ThemeEntry>,
+    pub keymap: BTreeMap<String, String>,
+    pub config_path: Option<PathBuf>,
+    // the settings file that was read, if any
 }
 
 impl Config {
+    pub fn from_args(args: &Args) -> Result<Config, Box<dyn Error>> {
+        // settings are layered with the file first, then
+        // environment variables, then command line flags
+        let mut config = Config::default();
+
+        let explicit = args.config.clone().or_else(|| env::var("MONEY_CONFIG").ok());
+        let path = explicit.clone().map(PathBuf::from).or_else(get_default_path);
+        if let Some(path) = path {
+            // only a file that was asked for has to exist
+            if path.exists() || explicit.is_some() {
+                config = config.apply_file(&ConfigFile::load(&path)?)?;
+                config.config_path = Some(path);
+            }
+        }
+
+        config = config.apply_env()?;
+
+        if let Some(database) = &args.database {
+            config = config.database(database);
+        }
+        if let Some(rate) = args.tick_rate {
+            config = config.tick_rate(rate);
+        }
+        if let Some(format) = &args.date_format {
+            config = config.date_format(format);
+        }
+        if let Some(currency) = &args.currency {
+            config = config.currency(currency);
+        }
+        if let Some(theme) = &args.theme {
+            config = config.theme(theme);
+        }
+        if args.no_effects {
+            config = config.effects(false);
+        }
+        if args.publish.is_some() {
+            config = config.publish(args.publish.clone());
+        }
+        for spec in &args.services {
+            config = config.service(ServiceSettings::parse(spec)?);
+        }
+
+        Ok(config
+            .service_endpoint(args.service_endpoint.clone())
+            .service_timeout(args.service_timeout)
+            .service_retries(args.service_retries)
+            .service_legacy(args.service_legacy))
+    }
+
+    pub fn apply_file(mut self, file: &ConfigFile) -> Result<Config, Box<dyn Error>> {
+        if let Some(database) = &file.database {
+            self = self.database(database);
+        }
+        if let Some(state) = &file.state {
+            self.state = Some(PathBuf::from(state));
+        }
+        if let Some(rate) = file.tick_rate {
+            self = self.tick_rate(rate);
+        }
+        if let Some(format) = &file.date_format {
+            self = self.date_format(format);
+        }
+        if let Some(currency) = &file.currency {
+            self = self.currency(currency);
+        }
+        if let Some(theme) = &file.theme {
+            self = self.theme(theme);
+        }
+        if let Some(effects) = file.effects {
+            self = self.effects(effects);
+        }
+        if file.publish.is_some() {
+            self = self.publish(file.publish.clone());
+        }
+        if let Some(assets) = &file.assets_account {
+            self.assets_account = assets.clone();
+        }
+        if let Some(liabilities) = &file.liabilities_account {
+            self.liabilities_account = liabilities.clone();
+        }
+        if let Some(income) = &file.income_account {
+            self.income_account = income.clone();
+        }
+        if let Some(expenses) = &file.expenses_account {
+            self.expenses_account = expenses.clone();
+        }
+        for (action, key) in &file.keymap {
+            self.keymap.insert(action.clone(), key.clone());
+        }
+        for (name, theme) in &file.themes {
+            self.themes.insert(name.clone(), theme.clone());
+        }
+        for entry in &file.services {
+            self = self.service(entry.to_settings()?);
+        }
+
+        Ok(self)
+    }
+
+    pub fn apply_env(mut self) -> Result<Config, Box<dyn Error>> {
+        // MONEY_SERVICE holds service specs like --service, separated by ;
+        if let Ok(database) = env::var("MONEY_DATABASE") {
+            self = self.database(database);
+        }
+        if let Ok(state) = env::var("MONEY_STATE") {
+            self.state = Some(PathBuf::from(state));
+        }
+        if let Ok(rate) = env::var("MONEY_TICK_RATE") {
+            let rate = rate.parse().map_err(|_| format!("MONEY_TICK_RATE of {} is not a number", rate))?;
+            self = self.tick_rate(rate);
+        }
+        if let Ok(format) = env::var("MONEY_DATE_FORMAT") {
+            self = self.date_format(&format);
+        }
+        if let Ok(currency) = env::var("MONEY_CURRENCY") {
+            self = self.currency(&currency);
+        }
+        // NO_COLOR is the common way to ask every program for no color,
+        // see https://no-color.org
+        if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+            self = self.theme("monochrome");
+        }
+        if let Ok(theme) = env::var("MONEY_THEME") {
+            self = self.theme(&theme);
+        }
+        if let Ok(publish) = env::var("MONEY_PUBLISH") {
+            self = self.publish(Some(publish));
+        }
+        if let Ok(services) = env::var("MONEY_SERVICE") {
+            for spec in services.split(';').filter(|spec| !spec.trim().is_empty()) {
+                self = self.service(ServiceSettings::parse(spec)?);
+            }
+        }
+
+        Ok(self)
+    }
+
+    pub fn to_file(&self) -> ConfigFile {
+        // the settings in effect, written the way the settings file holds them
+        ConfigFile {
+            database: Some(self.database.display().to_string()),
+            state: self.state.as_ref().map(|state| state.display().to_string()),
+            tick_rate: Some(self.tick_rate.as_millis() as u64),
+            date_format: Some(self.date_format.clone()),
+            currency: Some(self.currency.clone()),
+            theme: Some(self.theme.clone()),
+            effects: Some(self.effects),
+            publish: self.publish.clone(),
+            assets_account: Some(self.assets_account.clone()),
+            liabilities_account: Some(self.liabilities_account.clone()),
+            income_account: Some(self.income_account.clone()),
+            expenses_account: Some(self.expenses_account.clone()),
+            keymap: self.keymap.clone(),
+            themes: self.themes.clone(),
+            services: self.services.services
+                .iter()
+                .map(|service| ServiceEntry {
+                    name: service.name.clone(),
+                    endpoint: service.endpoint.clone(),
+                    transport: Some(service.transport.clone()),
+                    timeout: Some(service.timeout.as_millis() as u64),
+                    retries: Some(service.retries),
+                    actions: Some(service.actions.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+
+    pub fn format_amount(&self, amount: f64) -> String {
+        format!("{}{:.2}", self.currency, amount)
+    }
+
+    pub fn date_format(mut self, format: &str) -> Config
+    {
+        self.date_format = format.to_string();
+        self
+    }
+
+    pub fn currency(mut self, currency: &str) -> Config
+    {
+        self.currency = currency.to_string();
+        self
+    }
+
+    pub fn theme(mut self, theme: &str) -> Config
+    {
+        self.theme = theme.to_string();
+        self
+    }
+
+    pub fn effects(mut self, effects: bool) -> Config
+    {
+        self.effects = effects;
+        self
+    }
+
     pub fn tick_rate(mut self, rate: u64) -> Config
     {
         self.tick_rate = Duration::from_millis(rate);
         self
     }
 
+    pub fn service(mut self, service: ServiceSettings) -> Config
+    {
+        self.services.insert(service);
+        self
+    }
+
+    // the service_ settings below adjust the report service
+
+    pub fn service_endpoint(mut self, endpoint: Option<String>) -> Config
+    {
+        if let Some(endpoint) = endpoint {
+            self.services.get_or_insert("report").endpoint = endpoint;
+        }
+        self
+    }
+
+    pub fn service_timeout(mut self, timeout: Option<u64>) -> Config
+    {
+        if let Some(timeout) = timeout {
+            self.services.get_or_insert("report").timeout = Duration::from_millis(timeout);
+        }
+        self
+    }
+
+    pub fn service_retries(mut self, retries: Option<usize>) -> Config
+    {
+        if let Some(retries) = retries {
+            self.services.get_or_insert("report").retries = retries;
+        }
+        self
+    }
+
+    pub fn service_legacy(mut self, legacy: bool) -> Config
+    {
+        if legacy {
+            self.services.get_or_insert("report").transport = "zmq-legacy".to_string();
+        }
+        self
+    }
+
+    pub fn publish(mut self, endpoint: Option<String>) -> Config
+    {
+        self.publish = endpoint;
+        self
+    }
+
     pub fn database<T>(mut self, path: T) -> Config
     where T: Into<PathBuf>
     {
@@ -41,8 +506,37 @@ impl Config {
 impl Default for Config {
     fn default() -> Config {
         Config {
-            database: PathBuf::new(),
-            tick_rate: Duration::new(0, 0),
+            database: PathBuf::from("data/db.json"),
+            state: state::get_default_path(),
+            tick_rate: Duration::from_millis(200),
+            services: ServiceRegistry::default(),
+            publish: None,
+            date_format: "%Y-%m-%d".to_string(),
+            currency: "".to_string(),
+            theme: "default".to_string(),
+            effects: true,
+            assets_account: "Assets".to_string(),
+            liabilities_account: "Liabilities".to_string(),
+            income_account: "Income".to_string(),
+            expenses_account: "Expenses".to_string(),
+            themes: BTreeMap::new(),
+            keymap: BTreeMap::new(),
+            config_path: None,
         }
     }
 }
+
+impl ConfigFile {
+
+    pub fn load(path: &Path) -> Result<ConfigFile, Box<dyn Error>> {
+        let text = file_io::read(path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+
+        toml::from_str(&text).map_err(|err| format!("{} is not valid: {}", path.display(), err).into())
+    }
+}
+
+pub fn get_default_path() -> Option<PathBuf> {
+    // config.toml under $XDG_CONFIG_HOME/money, or the platform equivalent
+    dirs::config_dir().map(|dir| dir.join("money").join("config.toml"))
+}
@@ -19,6 +19,9 @@ use std::{
 use crate::app::{App, InputMode, Focus};
 use crate::ui::ui;
 use crate::config::Config;
+use crate::keymap::{Action, Key};
+use crate::mouse::on_mouse;
+use crate::command;
 
 
 pub fn run_app<B: Backend>(
@@ -31,6 +34,7 @@ pub fn run_app<B: Backend>(
 
     // attempt to load the database
     app = app.load_company()?;
+    app.check_recurrences();
 
     loop {
 
@@ -44,51 +48,50 @@ pub fn run_app<B: Backend>(
             .unwrap_or_else(|| Duration::from_secs(0)); // times out on error
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match app.input_mode {
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('e') => {
-                            app.input_mode = InputMode::Editing;
-                        }
-                        KeyCode::Char('q') => {
+            match event::read()? {
+                Event::Key(key) => {
+                    match app.input_mode {
+                        InputMode::Normal => {
+                            if let Some(action) = app.press_key(Key::from_event(&key)) {
+                                if perform(&mut app, action) {
+                                    return Ok(());
+                                }
+                            }
+                        },
+                        InputMode::Editing => match key.code {
+                            KeyCode::Enter => {
+                                app.submit_input();
+                            }
+                            KeyCode::Char(c) => {
+                                app.input.push(c);
+                            }
+                            KeyCode::Backspace => {
+                                app.input.pop();
+                            }
+                            KeyCode::Esc => {
+                                app.stop_editing();
+                            }
+                            KeyCode::Tab => {
+                                command::complete(&mut app);
+                            }
+                            KeyCode::Up => {
+                                command::recall(&mut app, true);
+                            }
+                            KeyCode::Down => {
+                                command::recall(&mut app, false);
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+                Event::Mouse(mouse) => {
+                    if let Some(action) = on_mouse(&mut app, &mouse) {
+                        if perform(&mut app, action) {
                             return Ok(());
                         }
-                        KeyCode::Char('l') => {
-                            app.toggle_focus(Focus::Charts);
-                        }
-                        KeyCode::Char('g') => {
-                            app.toggle_focus(Focus::ExpenseReport);
-                        }
-                        KeyCode::Char('b') => {
-                            app.toggle_focus(Focus::BalanceSheet);
-                        }
-                        KeyCode::Char('t') => {
-                            app.toggle_focus(Focus::NewTransaction);
-                        }
-                        KeyCode::Char('r') => {
-                            app.toggle_focus(Focus::Register);
-                        }
-                        KeyCode::Char('d') => {
-                            app.toggle_focus(Focus::DeleteTransaction);
-                        }
-                        _ => {}
-                    },
-                    InputMode::Editing => match key.code {
-                        KeyCode::Enter => {
-                            app.messages.push(app.input.drain(..).collect());
-                        }
-                        KeyCode::Char(c) => {
-                            app.input.push(c);
-                        }
-                        KeyCode::Backspace => {
-                            app.input.pop();
-                        }
-                        KeyCode::Esc => {
-                            app.input_mode = InputMode::Normal;
-                        }
-                        _ => {}
-                    },
+                    }
                 }
+                _ => {}
             }
         }
         if last_tick.elapsed() >= config.tick_rate {
@@ -96,4 +99,117 @@ pub fn run_app<B: Backend>(
             last_tick = Instant::now();
         }
     }
-}
\ No newline at end of file
+}
+
+fn perform(app: &mut App, action: Action) -> bool {
+    // does what an action asks for, whether its key was pressed or the
+    // mouse picked it. returns true to quit
+    // the warning about quitting the sandbox only holds for the next action
+    if action != Action::Quit {
+        app.quit_warned = false;
+    }
+
+    match action {
+        Action::Edit => {
+            app.input_mode = InputMode::Editing;
+        }
+        Action::Command => {
+            app.start_command();
+        }
+        Action::Quit if app.request_quit() => {
+            return true;
+        }
+        Action::WhatsNew => {
+            app.toggle_whats_new();
+        }
+        Action::Help => {
+            app.toggle_help();
+        }
+        Action::Dismiss if app.focus == Focus::Help => {
+            app.close_help();
+        }
+        Action::Dismiss => {
+            app.dismiss_notices();
+        }
+        Action::Undo => {
+            app.undo();
+        }
+        Action::Redo => {
+            app.redo();
+        }
+        Action::CyclePeriod => {
+            app.cycle_report_period();
+        }
+        Action::Approve => {
+            app.approve_recurrences();
+        }
+        Action::Up if app.focus == Focus::Help => {
+            app.help_select_previous();
+        }
+        Action::Down if app.focus == Focus::Help => {
+            app.help_select_next();
+        }
+        Action::Select if app.focus == Focus::Help => {
+            app.open_help_topic();
+        }
+        Action::Up if app.focus == Focus::WhatsNew => {
+            app.notice_select_previous();
+        }
+        Action::Down if app.focus == Focus::WhatsNew => {
+            app.notice_select_next();
+        }
+        Action::Select if app.focus == Focus::WhatsNew => {
+            app.open_notice();
+        }
+        Action::Up if app.focus == Focus::Charts => {
+            app.chart_select_previous();
+        }
+        Action::Down if app.focus == Focus::Charts => {
+            app.chart_select_next();
+        }
+        Action::Left => {
+            app.chart_collapse();
+        }
+        Action::Right => {
+            app.chart_expand();
+        }
+        Action::Select => {
+            app.chart_open_register();
+        }
+        Action::AddAccount => {
+            app.chart_begin("add");
+        }
+        Action::RenameAccount => {
+            app.chart_begin("rename");
+        }
+        Action::Up => {
+            if let Some(reconciliation) = app.reconciliation.as_mut() {
+                reconciliation.select_previous();
+            }
+        }
+        Action::Down => {
+            if let Some(reconciliation) = app.reconciliation.as_mut() {
+                reconciliation.select_next(&app.company);
+            }
+        }
+        Action::ToggleCleared => {
+            if let Some(reconciliation) = app.reconciliation.as_mut() {
+                reconciliation.toggle_selected(&app.company);
+            }
+        }
+        Action::FinishReconciliation => {
+            app.finish_reconciliation();
+        }
+        Action::CancelService => {
+            app.cancel_service_report();
+        }
+        action => {
+            // the rest open or close their view
+            if let Some(focus) = App::get_view(action) {
+                app.toggle_focus(focus);
+            }
+        }
+    }
+
+    false
+}
@@ -0,0 +1,107 @@
+// events announce each change to the ledger on a ZMQ PUB socket so other
+// services can keep up without re-reading the database.
+// see "Change events" in the README
+
+use chrono::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use crate::company::Company;
+
+// One change to the ledger. seq counts up from 1 for each publisher
+// so subscribers can tell when they missed something
+#[derive(Debug, Serialize, Clone)]
+pub struct Event {
+    pub seq: u64,
+    pub r#type: String,
+    // the kind of record followed by "_added", "_deleted" or "_changed",
+    // where the kinds are account, transaction, budget and recurrence
+    pub reason: String,
+    // what the user did, such as "delete transaction" or "undo"
+    pub time: String,
+    pub data: Value,
+}
+
+pub struct Publisher {
+    socket: zmq::Socket,
+    seq: u64,
+}
+
+impl Publisher {
+
+    pub fn bind(endpoint: &str) -> Result<Self, Box<dyn Error>> {
+        let ctx = zmq::Context::new();
+        let socket = ctx.socket(zmq::PUB)?;
+        socket.bind(endpoint)?;
+
+        Ok(Publisher { socket, seq: 0 })
+    }
+
+    pub fn publish_changes(&mut self, reason: &str, before: &Company, after: &Company) -> usize {
+        // sends an event for everything that differs and returns how many
+        let changes = diff(before, after);
+        let count = changes.len();
+
+        for (kind, data) in changes {
+            self.seq += 1;
+            let event = Event {
+                seq: self.seq,
+                r#type: kind,
+                reason: reason.to_string(),
+                time: Local::now().to_rfc3339(),
+                data,
+            };
+            // the type goes first so subscribers can filter on it
+            if let Ok(payload) = serde_json::to_string(&event) {
+                let _ = self.socket.send_multipart([event.r#type.as_bytes(), payload.as_bytes()], 0);
+            }
+        }
+
+        count
+    }
+}
+
+pub fn diff(before: &Company, after: &Company) -> Vec<(String, Value)> {
+    // accounts, transactions, budgets then recurrences, each as added,
+    // deleted or changed
+    let mut changes = Vec::new();
+
+    let accounts = |company: &Company| to_values(company.accounts.iter().map(|account| (&account.id, account)));
+    let transactions = |company: &Company| to_values(company.transactions.iter().map(|transaction| (&transaction.id, transaction)));
+    let budgets = |company: &Company| to_values(company.budgets.iter().map(|budget| (&budget.id, budget)));
+    let recurrences = |company: &Company| to_values(company.recurrences.iter().map(|recurrence| (&recurrence.id, recurrence)));
+
+    compare("account", &accounts(before), &accounts(after), &mut changes);
+    compare("transaction", &transactions(before), &transactions(after), &mut changes);
+    compare("budget", &budgets(before), &budgets(after), &mut changes);
+    compare("recurrence", &recurrences(before), &recurrences(after), &mut changes);
+
+    changes
+}
+
+fn to_values<'a, T, I>(records: I) -> BTreeMap<String, Value>
+where
+    T: Serialize + 'a,
+    I: Iterator<Item = (&'a String, &'a T)>,
+{
+    // each record as stored, by id
+    records
+        .map(|(id, record)| (id.clone(), serde_json::to_value(record).unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn compare(kind: &str, before: &BTreeMap<String, Value>, after: &BTreeMap<String, Value>, changes: &mut Vec<(String, Value)>) {
+    for (id, value) in after {
+        match before.get(id) {
+            None => changes.push((format!("{}_added", kind), value.clone())),
+            Some(old) if old != value => changes.push((format!("{}_changed", kind), value.clone())),
+            _ => {},
+        }
+    }
+
+    for id in before.keys().filter(|id| !after.contains_key(*id)) {
+        changes.push((format!("{}_deleted", kind), json!({ "id": id })));
+    }
+}
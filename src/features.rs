@@ -0,0 +1,290 @@
+// the features added to Money, newest last, so each one can be announced
+// with its benefits, costs and a short tutorial the first time a user
+// opens a version that has it
+
+use crate::keymap::Action;
+
+// the version running now
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// the version whose features are announced to a user who has never run
+// Money before. everything up to it is the basics the README describes
+pub const FIRST_VERSION: &str = "0.1.0";
+
+pub struct Feature {
+    pub version: &'static str,
+    // the first version that has it
+    pub name: &'static str,
+    pub action: Option<Action>,
+    // what opens it in the interface, if anything
+    pub benefit: &'static str,
+    pub cost: &'static str,
+    // what it takes, or what can go wrong
+    pub tutorial: &'static [&'static str],
+}
+
+pub const FEATURES: [Feature; 21] = [
+    Feature {
+        version: "0.2.0",
+        name: "Budgets",
+        action: Some(Action::Budget),
+        benefit: "See spending against a budget for each account by month, quarter or year",
+        cost: "None, budgets only add a report",
+        tutorial: &[
+            "Open the budget view and enter set Expenses:Food 300 month",
+            "Compare it with what was spent",
+            "Change the period to see the quarter or the year",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Scheduled transactions",
+        action: Some(Action::Upcoming),
+        benefit: "Rent, pay and bills are entered for you when they fall due",
+        cost: "Nothing is posted until you approve it, so due items wait for you",
+        tutorial: &[
+            "Open the scheduled transactions and enter add monthly 2014-12-01 Expenses:Rent Assets 1200 rent",
+            "See what is due and what is coming",
+            "Approve to post everything that is due",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Reconciliation",
+        action: Some(Action::Reconcile),
+        benefit: "Check the ledger against a bank statement, one transaction at a time",
+        cost: "Deleting a reconciled transaction asks twice, because the bank has already seen it",
+        tutorial: &[
+            "Open the reconciliation and enter an account_id, the statement date and its balance",
+            "Tick each transaction that appears on the statement",
+            "Finish once the difference is zero to mark them cleared",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Payees, tags and metadata",
+        action: Some(Action::Groups),
+        benefit: "Group spending by who was paid, by tag or by any key you add",
+        cost: "Transactions only group as well as they are tagged",
+        tutorial: &[
+            "Give transactions a payee, tags or metadata in the database or through money serve",
+            "Open the groups view to see totals for each",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Queries",
+        action: Some(Action::Filter),
+        benefit: "Narrow every report to the transactions you care about",
+        cost: "A query stays in effect until it is cleared, so reports show less than everything",
+        tutorial: &[
+            "Open the query and enter terms such as account:Expenses:Food and amount>50",
+            "The balance, budget and group views follow the query",
+            "Enter nothing to clear it",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Account tree",
+        action: Some(Action::Charts),
+        benefit: "Fold, add, rename, move, merge and close accounts from the chart of accounts",
+        cost: "Merging moves every transaction, so use undo if the wrong account was merged",
+        tutorial: &[
+            "Open the chart of accounts and move through it",
+            "Fold and unfold accounts with subaccounts",
+            "Add a subaccount, rename one, or enter move, merge or close followed by an account_id",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Undo and redo",
+        action: None,
+        benefit: "Take back any change to the ledger, and redo it if that was a mistake too",
+        cost: "The history only lasts until Money is closed",
+        tutorial: &[
+            "Make a change, such as adding a transaction",
+            "Undo to take it back, and redo to put it back",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Cash flow statement",
+        action: Some(Action::CashFlow),
+        benefit: "See where cash came from and went, by operating, investing and financing",
+        cost: "Only accounts marked as cash are followed",
+        tutorial: &[
+            "Mark cash accounts from the chart of accounts with: cash <account_id> on",
+            "Open the cash flow statement and change the period",
+            "Or run money cash-flow from the command line",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Dashboard",
+        action: Some(Action::Dashboard),
+        benefit: "Chart net worth, the balance of any account, and income against expenses",
+        cost: "The charts need a wide terminal to be readable",
+        tutorial: &[
+            "Open the dashboard",
+            "Pick the account to chart from the chart of accounts",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Expense report export",
+        action: Some(Action::ExpenseReport),
+        benefit: "Save the expense report as CSV, HTML or Markdown, or have a service build it",
+        cost: "An export replaces a file of the same name",
+        tutorial: &[
+            "Open the expense report and change the period",
+            "Enter csv, html or md, and optionally a path, to export it",
+            "Enter service to have the report service build it instead",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Report service",
+        action: None,
+        benefit: "Build reports in another process, with timeouts, retries and cancelling",
+        cost: "The service has to be started first, see Report service in the README",
+        tutorial: &[
+            "Run cargo run --bin report_service -- --output reports",
+            "Open the expense report and enter service",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Ledger service",
+        action: None,
+        benefit: "Let other programs read and change the ledger, and follow every change",
+        cost: "Anything that can reach the address can change the ledger",
+        tutorial: &[
+            "Run money serve, and add --publish to announce changes",
+            "See Ledger service and Change events in the README for the requests",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Settings file",
+        action: None,
+        benefit: "Keep the database, date format, currency and services in one place",
+        cost: "A mistake in the file stops Money from starting until it is fixed",
+        tutorial: &[
+            "Write ~/.config/money/config.toml, see Settings in the README",
+            "Run money config show to check what is in effect",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Keymap",
+        action: None,
+        benefit: "Rebind any key, use chords, and move with j and k",
+        cost: "The menu follows your keys, so it will not match the README if you change them",
+        tutorial: &[
+            "Add a [keymap] table to the settings file, such as undo = \"ctrl-z\"",
+            "See Keymap in the README for every action",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Themes",
+        action: None,
+        benefit: "Dark, light, high-contrast and monochrome colors, and no blinking if you prefer",
+        cost: "Some terminals cannot show every color of the high-contrast theme",
+        tutorial: &[
+            "Start Money with --theme high-contrast, or set theme in the settings file",
+            "Add --no-effects to stop the blinking",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Manual",
+        action: Some(Action::Help),
+        benefit: "Read about any view from inside it, search the manual, or print it with money help",
+        cost: "money help no longer prints the usage of a command, use money <command> --help for that",
+        tutorial: &[
+            "Open the manual from any view to read about that view",
+            "Go back to the list of topics, or search it for a word",
+            "Run money help <topic> to print a topic",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Sandbox",
+        action: Some(Action::Sandbox),
+        benefit: "Try out changes or an import on a copy of the ledger and compare the balances before keeping them",
+        cost: "Nothing is saved until the sandbox is committed, so changes are lost if it is discarded",
+        tutorial: &[
+            "Open the sandbox and enter start",
+            "Add or delete transactions, or enter import <path> to bring in another ledger",
+            "Come back to the sandbox to compare balances, then enter commit or discard",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Mouse",
+        action: None,
+        benefit: "Click the menu, rows and buttons, and scroll with the wheel",
+        cost: "While Money has the mouse, select text by holding shift, if your terminal allows it",
+        tutorial: &[
+            "Click an entry of the menu to open it",
+            "Click a row of the chart of accounts, then click it again to open its register",
+            "Scroll the messages with the wheel",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Command line",
+        action: Some(Action::Command),
+        benefit: "Add transactions, list registers and balances from any view without leaving it",
+        cost: "Commands are kept in the state file so they can be recalled",
+        tutorial: &[
+            "Enter :bal --period month to list the balances of the latest month with transactions",
+            "Type :reg Exp and press Tab to complete the account, then Enter",
+            "Type : and press Up to bring back an earlier command",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "Tutorial",
+        action: None,
+        benefit: "Learn to add a transaction, read a register and the balance sheet, and undo, on a sample ledger",
+        cost: "It runs as its own session, so quit Money first",
+        tutorial: &[
+            "Run money tutorial",
+            "Follow the steps in the panel beside the messages",
+        ],
+    },
+    Feature {
+        version: "0.2.0",
+        name: "What's new",
+        action: Some(Action::WhatsNew),
+        benefit: "Hear about new features once, with a way to try each of them",
+        cost: "It opens by itself after an update until it is dismissed",
+        tutorial: &[
+            "Pick a feature and open it to try it",
+            "Dismiss the list, and open it again whenever you like",
+        ],
+    },
+];
+
+pub fn get_new_since(version: Option<&str>) -> Vec<&'static Feature> {
+    // everything after the version last seen, or after the first
+    // version for someone who has never run Money
+    let since = version.unwrap_or(FIRST_VERSION);
+
+    FEATURES
+        .iter()
+        .filter(|feature| is_newer(feature.version, since))
+        .collect()
+}
+
+pub fn is_newer(version: &str, than: &str) -> bool {
+    // compares dotted versions number by number, so 0.10.0 is newer than 0.9.0
+    let parse = |version: &str| version
+        .split('.')
+        .map(|part| part.parse::<u64>().unwrap_or(0))
+        .collect::<Vec<u64>>();
+
+    parse(version) > parse(than)
+}
@@ -4,6 +4,7 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::io;
 use std::path::Path;
+use std::time::SystemTime;
 
 pub fn read<P: AsRef<Path>>(file_path: P) -> io::Result<String> {
     let utf8_vector = fs::read(file_path.as_ref())?;
@@ -15,8 +16,8 @@ pub fn read<P: AsRef<Path>>(file_path: P) -> io::Result<String> {
 pub fn write(file_path: &str, thing_to_write: &str) -> io::Result<()> {
     let mut f = OpenOptions::new().write(true).append(true).open(file_path)?;
 
-    f.write(thing_to_write.as_bytes())?;
-    f.write("\n".as_bytes())?;
+    f.write_all(thing_to_write.as_bytes())?;
+    f.write_all("\n".as_bytes())?;
     
     Ok(())
 }
@@ -24,4 +25,8 @@ pub fn write(file_path: &str, thing_to_write: &str) -> io::Result<()> {
 pub fn truncate(file_path: &str) -> io::Result<()> {
     OpenOptions::new().write(true).create(true).truncate(true).open(file_path)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn get_modified<P: AsRef<Path>>(file_path: P) -> Option<SystemTime> {
+    fs::metadata(file_path.as_ref()).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,219 @@
+use chrono::prelude::*;
+use crate::transaction::Transaction;
+
+// How a condition compares the transaction against its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Contains,
+}
+
+impl Comparison {
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Contains => "~",
+        }
+    }
+
+    fn compare<T: PartialOrd>(&self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Equal | Comparison::Contains => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+
+    fn compare_text(&self, left: &str, right: &str) -> bool {
+        // text is compared without regard to case
+        let left = left.to_lowercase();
+        let right = right.to_lowercase();
+
+        match self {
+            Comparison::Contains => left.contains(&right),
+            _ => self.compare(&left[..], &right[..]),
+        }
+    }
+}
+
+// A single test a transaction has to pass
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Account(Comparison, String, Vec<String>),
+    // the account path as written and the ids of the accounts it matched
+    // with their subaccounts
+    Amount(Comparison, f64),
+    Date(Comparison, NaiveDate, NaiveDate),
+    // a date may name a whole month or year, so it covers a first and last day
+    Memo(Comparison, String),
+    Payee(Comparison, String),
+    Tag(Comparison, String),
+    Metadata(Comparison, String, String),
+    // an empty value matches any transaction carrying the key
+}
+
+impl Condition {
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        match self {
+            Condition::Account(comparison, _, ids) => {
+                let found = ids.contains(&transaction.debit) || ids.contains(&transaction.credit);
+                match comparison {
+                    Comparison::NotEqual => !found,
+                    _ => found,
+                }
+            },
+            Condition::Amount(comparison, amount) => {
+                let value = transaction.amount.parse::<f64>().unwrap_or(0.0);
+                comparison.compare(value, *amount)
+            },
+            Condition::Date(comparison, first, last) => {
+                let date = transaction.date;
+                match comparison {
+                    Comparison::Equal | Comparison::Contains => date >= *first && date <= *last,
+                    Comparison::NotEqual => date < *first || date > *last,
+                    Comparison::Less => date < *first,
+                    Comparison::LessOrEqual => date <= *last,
+                    Comparison::Greater => date > *last,
+                    Comparison::GreaterOrEqual => date >= *first,
+                }
+            },
+            Condition::Memo(comparison, memo) => comparison.compare_text(&transaction.memo, memo),
+            Condition::Payee(comparison, payee) => comparison.compare_text(&transaction.payee, payee),
+            Condition::Tag(comparison, tag) => {
+                let found = match comparison {
+                    Comparison::Contains => transaction.tags
+                        .iter()
+                        .any(|element| element.to_lowercase().contains(&tag.to_lowercase())),
+                    _ => transaction.has_tag(tag),
+                };
+                match comparison {
+                    Comparison::NotEqual => !found,
+                    _ => found,
+                }
+            },
+            Condition::Metadata(comparison, key, value) => {
+                let found = match (transaction.metadata.get(key), comparison) {
+                    (None, _) => false,
+                    (Some(_), _) if value.is_empty() => true,
+                    (Some(found), Comparison::Contains) => found.to_lowercase().contains(&value.to_lowercase()),
+                    (Some(found), _) => found.eq_ignore_ascii_case(value),
+                };
+                match comparison {
+                    Comparison::NotEqual => !found,
+                    _ => found,
+                }
+            },
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        // the condition written back out in the query language
+        match self {
+            Condition::Account(comparison, path, _) => format!("account{}{}", comparison.symbol(), quote(path)),
+            Condition::Amount(comparison, amount) => format!("amount{}{}", comparison.symbol(), amount),
+            Condition::Date(comparison, first, last) => {
+                let date = if first == last {
+                    first.to_string()
+                }
+                else if first.month() == last.month() {
+                    first.format("%Y-%m").to_string()
+                }
+                else {
+                    first.format("%Y").to_string()
+                };
+                format!("date{}{}", comparison.symbol(), date)
+            },
+            Condition::Memo(comparison, memo) => format!("memo{}{}", comparison.symbol(), quote(memo)),
+            Condition::Payee(comparison, payee) => format!("payee{}{}", comparison.symbol(), quote(payee)),
+            Condition::Tag(Comparison::Equal, tag) => format!("#{}", tag),
+            Condition::Tag(comparison, tag) => format!("tag{}{}", comparison.symbol(), quote(tag)),
+            Condition::Metadata(comparison, key, value) => {
+                if value.is_empty() {
+                    format!("meta{}{}", comparison.symbol(), key)
+                }
+                else {
+                    format!("meta{}{}={}", comparison.symbol(), key, quote(value))
+                }
+            },
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    if text.contains(' ') {
+        format!("\"{}\"", text)
+    }
+    else {
+        text.to_string()
+    }
+}
+
+// A Filter narrows the transactions that reports are run against.
+// every condition must match
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub conditions: Vec<Condition>,
+}
+
+impl Filter {
+
+    pub fn new() -> Self {
+        Filter::default()
+    }
+
+    pub fn set_payee(&mut self, payee: &str) -> &mut Self {
+        self.conditions.retain(|condition| !matches!(condition, Condition::Payee(..)));
+        self.conditions.push(Condition::Payee(Comparison::Equal, payee.to_string()));
+
+        self
+    }
+
+    pub fn add_tag(&mut self, tag: &str) -> &mut Self {
+        self.conditions.push(Condition::Tag(Comparison::Equal, tag.trim_start_matches('#').to_string()));
+
+        self
+    }
+
+    pub fn add_metadata(&mut self, key: &str, value: &str) -> &mut Self {
+        self.conditions.push(Condition::Metadata(Comparison::Equal, key.to_string(), value.to_string()));
+
+        self
+    }
+
+    pub fn add_condition(&mut self, condition: Condition) -> &mut Self {
+        self.conditions.push(condition);
+
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(transaction))
+    }
+
+    pub fn describe(&self) -> String {
+        self.conditions
+            .iter()
+            .map(|condition| condition.describe())
+            .collect::<Vec<String>>()
+            .join(" and ")
+    }
+}
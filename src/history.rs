@@ -0,0 +1,57 @@
+use crate::company::Company;
+
+// History keeps snapshots of the Company so changes can be undone and redone
+#[derive(Debug, Default)]
+pub struct History {
+    pub undo: Vec<(String, Company)>,
+    pub redo: Vec<(String, Company)>,
+}
+
+// the oldest snapshots are dropped past this many
+const MAX_SNAPSHOTS: usize = 50;
+
+impl History {
+
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    pub fn record(&mut self, label: &str, company: &Company) {
+        // call before changing the company, then confirm once the change
+        // has gone through or rollback when it failed
+        self.undo.push((label.to_string(), company.clone()));
+    }
+
+    pub fn confirm(&mut self) {
+        // a new change clears the redo list
+        self.redo.clear();
+        if self.undo.len() > MAX_SNAPSHOTS {
+            self.undo.drain(..self.undo.len() - MAX_SNAPSHOTS);
+        }
+    }
+
+    pub fn rollback(&mut self, company: &mut Company) {
+        // puts back the last snapshot when the change it was taken for failed.
+        // the redo list is left as it was
+        if let Some((_, snapshot)) = self.undo.pop() {
+            *company = snapshot;
+        }
+    }
+
+    pub fn undo(&mut self, company: &mut Company) -> Option<String> {
+        // restores the last snapshot and returns what was undone
+        let (label, snapshot) = self.undo.pop()?;
+        let current = std::mem::replace(company, snapshot);
+        self.redo.push((label.clone(), current));
+
+        Some(label)
+    }
+
+    pub fn redo(&mut self, company: &mut Company) -> Option<String> {
+        let (label, snapshot) = self.redo.pop()?;
+        let current = std::mem::replace(company, snapshot);
+        self.undo.push((label.clone(), current));
+
+        Some(label)
+    }
+}
@@ -0,0 +1,311 @@
+// the keymap turns key presses into actions, so every shortcut can be
+// rebound from the [keymap] table of the settings file.
+// see "Keymap" in the README
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+// Everything a key can do in the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    BalanceSheet,
+    NewTransaction,
+    Register,
+    DeleteTransaction,
+    EditTransaction,
+    Charts,
+    ExpenseReport,
+    Budget,
+    CashFlow,
+    Dashboard,
+    Upcoming,
+    Reconcile,
+    Groups,
+    Filter,
+    Sandbox,
+    WhatsNew,
+    Help,
+    Undo,
+    Redo,
+    Edit,
+    Command,
+    Quit,
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    AddAccount,
+    RenameAccount,
+    CyclePeriod,
+    Approve,
+    ToggleCleared,
+    FinishReconciliation,
+    CancelService,
+    Dismiss,
+}
+
+// name in the settings file, default keys and what it does.
+// the global actions come first, in the order the menu lists them
+const ACTIONS: [(Action, &str, &str, &str); 35] = [
+    (Action::BalanceSheet, "balance_sheet", "b", "List the current balance for your portfolio"),
+    (Action::NewTransaction, "new_transaction", "t", "Enter a new transaction"),
+    (Action::Register, "register", "r", "Examine the register for an account"),
+    (Action::DeleteTransaction, "delete_transaction", "d", "Delete a transaction"),
+    (Action::EditTransaction, "edit_transaction", "m", "Edit a transaction or the status of a posting"),
+    (Action::Charts, "charts", "l", "Browse the chart of accounts"),
+    (Action::ExpenseReport, "expense_report", "g", "Generate an expense report"),
+    (Action::Budget, "budget", "v", "Compare spending against budgets"),
+    (Action::CashFlow, "cash_flow", "f", "Show the cash flow statement"),
+    (Action::Dashboard, "dashboard", "o", "Chart net worth, balances, income and expenses"),
+    (Action::Upcoming, "upcoming", "n", "Review scheduled transactions"),
+    (Action::Reconcile, "reconcile", "c", "Reconcile an account with a bank statement"),
+    (Action::Groups, "groups", "y", "Group transactions by payee, tag and metadata"),
+    (Action::Filter, "filter", "/", "Query transactions to filter the reports"),
+    (Action::Sandbox, "sandbox", "s", "Try changes in a sandbox, then keep or discard them"),
+    (Action::WhatsNew, "whats_new", "w", "List what is new and try it"),
+    (Action::Help, "help", "?", "Read the manual, starting with the open view"),
+    (Action::Undo, "undo", "u", "Undo the last change"),
+    (Action::Redo, "redo", "U", "Redo the last undone change"),
+    (Action::Edit, "edit", "e", "Start editing"),
+    (Action::Command, "command", ":", "Run a command such as :add, :reg or :bal"),
+    (Action::Quit, "quit", "q, Z Z", "Quit the program"),
+    (Action::Up, "up", "Up, k", "Move up a list"),
+    (Action::Down, "down", "Down, j", "Move down a list"),
+    (Action::Left, "left", "Left, h", "Fold an account"),
+    (Action::Right, "right", "Right", "Unfold an account"),
+    (Action::Select, "select", "Enter", "Open the register of an account"),
+    (Action::AddAccount, "add_account", "a", "Add a subaccount"),
+    (Action::RenameAccount, "rename_account", "R", "Rename an account"),
+    (Action::CyclePeriod, "cycle_period", "p", "Change the period of a report"),
+    (Action::Approve, "approve", "a", "Post all due transactions"),
+    (Action::ToggleCleared, "toggle_cleared", "Space", "Tick a transaction off the statement"),
+    (Action::FinishReconciliation, "finish_reconciliation", "f", "Finish the reconciliation"),
+    (Action::CancelService, "cancel_service", "x", "Cancel the report service"),
+    (Action::Dismiss, "dismiss", "Esc", "Put away the manual, or what is new until the next version"),
+];
+
+impl Action {
+
+    pub fn all() -> impl Iterator<Item = Action> {
+        ACTIONS.iter().map(|(action, ..)| *action)
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|(_, candidate, ..)| *candidate == name).map(|(action, ..)| *action)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    pub fn description(self) -> &'static str {
+        self.entry().3
+    }
+
+    pub fn is_global(self) -> bool {
+        // the rest only do something in the view they belong to
+        !matches!(self,
+            Action::Up | Action::Down | Action::Left | Action::Right | Action::Select
+            | Action::AddAccount | Action::RenameAccount | Action::CyclePeriod | Action::Approve
+            | Action::ToggleCleared | Action::FinishReconciliation | Action::CancelService | Action::Dismiss)
+    }
+
+    fn entry(self) -> &'static (Action, &'static str, &'static str, &'static str) {
+        ACTIONS.iter().find(|(action, ..)| *action == self).expect("every action has an entry")
+    }
+}
+
+// One key press, such as "a", "ctrl-r" or "Enter"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Key {
+
+    pub fn parse(text: &str) -> Result<Key, Box<dyn Error>> {
+        let mut key = Key { code: KeyCode::Null, ctrl: false, alt: false };
+
+        let mut rest = text;
+        loop {
+            let lower = rest.to_lowercase();
+            if lower.starts_with("ctrl-") && rest.len() > 5 {
+                key.ctrl = true;
+                rest = &rest[5..];
+            }
+            else if lower.starts_with("alt-") && rest.len() > 4 {
+                key.alt = true;
+                rest = &rest[4..];
+            }
+            else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        key.code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match &rest.to_lowercase()[..] {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                "comma" => KeyCode::Char(','),
+                other => match other.strip_prefix('f').and_then(|number| number.parse::<u8>().ok()) {
+                    Some(number) if (1..=12).contains(&number) => KeyCode::F(number),
+                    _ => return Err(format!("{} is not a key", text).into()),
+                },
+            },
+        };
+
+        Ok(key)
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Key {
+        // shift is already in the character, so only ctrl and alt count
+        Key {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+            alt: event.modifiers.contains(KeyModifiers::ALT),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(',') => "Comma".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(number) => format!("F{}", number),
+            KeyCode::PageUp => "PageUp".to_string(),
+            KeyCode::PageDown => "PageDown".to_string(),
+            other => format!("{:?}", other),
+        };
+
+        format!("{}{}{}", if self.ctrl { "ctrl-" } else { "" }, if self.alt { "alt-" } else { "" }, name)
+    }
+}
+
+// The Keymap holds the keys bound to each action. a binding can be a
+// chord of several keys pressed one after another, such as "Z Z"
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    pending: Vec<Key>,
+    // the start of a chord waiting for its next key
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let mut keymap = Keymap { bindings: Vec::new(), pending: Vec::new() };
+        for (action, _, keys, _) in ACTIONS.iter() {
+            keymap.bind(*action, keys).expect("the default keys parse");
+        }
+
+        keymap
+    }
+}
+
+impl Keymap {
+
+    pub fn from_settings(settings: &BTreeMap<String, String>) -> Result<Keymap, Box<dyn Error>> {
+        // each entry replaces every default key of its action
+        let mut keymap = Keymap::default();
+        for (name, keys) in settings {
+            let action = Action::from_name(name).ok_or_else(|| format!("{} is not an action in the keymap", name))?;
+            keymap.bind(action, keys)?;
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn bind(&mut self, action: Action, keys: &str) -> Result<&mut Self, Box<dyn Error>> {
+        // keys are separated by commas, and the keys of a chord by spaces
+        let mut bindings = Vec::new();
+        for binding in keys.split(',').map(|binding| binding.trim()).filter(|binding| !binding.is_empty()) {
+            let chord = binding.split_whitespace().map(Key::parse).collect::<Result<Vec<Key>, _>>()?;
+            bindings.push((chord, action));
+        }
+        if bindings.is_empty() {
+            return Err(format!("{} needs at least one key", action.name()).into());
+        }
+
+        self.bindings.retain(|(_, bound)| *bound != action);
+        self.bindings.extend(bindings);
+
+        Ok(self)
+    }
+
+    pub fn press<F>(&mut self, key: Key, is_available: F) -> Option<Action>
+    where F: Fn(Action) -> bool
+    {
+        // an action that belongs to the current view wins over a global one
+        // with the same key. a key that starts a chord waits for the rest
+        self.pending.push(key);
+
+        let candidates = self.bindings
+            .iter()
+            .filter(|(chord, action)| chord.starts_with(&self.pending) && is_available(*action))
+            .collect::<Vec<_>>();
+
+        if candidates.iter().any(|(chord, _)| chord.len() > self.pending.len()) {
+            return None;
+        }
+
+        let found = candidates
+            .iter()
+            .filter(|(chord, _)| chord.len() == self.pending.len())
+            .map(|(_, action)| *action)
+            .min_by_key(|action| action.is_global());
+
+        let broken = self.pending.len() > 1;
+        self.pending.clear();
+
+        // the last key of a broken chord still counts on its own
+        match found {
+            None if broken => self.press(key, is_available),
+            found => found,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn get_pending(&self) -> String {
+        // the keys of the chord pressed so far, such as "Z"
+        self.pending.iter().map(|key| key.describe()).collect::<Vec<String>>().join(" ")
+    }
+
+    pub fn get_keys(&self, action: Action) -> String {
+        // every binding of the action, such as "Up/k"
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(chord, _)| chord.iter().map(|key| key.describe()).collect::<Vec<String>>().join(" "))
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    pub fn get_menu(&self) -> Vec<(Action, String, &'static str)> {
+        // the keys and description of every global action but editing,
+        // which the help line already shows
+        Action::all()
+            .filter(|action| action.is_global() && *action != Action::Edit)
+            .map(|action| (action, self.get_keys(action), action.description()))
+            .collect()
+    }
+}
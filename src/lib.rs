@@ -0,0 +1,37 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+// see https://stackoverflow.com/questions/48071513/how-to-use-one-module-from-another-module-in-a-rust-cargo-project/48071730#48071730
+// on combining modules and using sibling modules
+pub mod ui;
+pub mod company;
+pub mod config;
+pub mod file_io;
+pub mod account;
+pub mod transaction;
+pub mod budget;
+pub mod recurrence;
+pub mod reconcile;
+pub mod filter;
+pub mod query;
+pub mod history;
+pub mod cashflow;
+pub mod report;
+pub mod service;
+pub mod protocol;
+pub mod server;
+pub mod events;
+pub mod keymap;
+pub mod theme;
+pub mod state;
+pub mod features;
+pub mod manual;
+pub mod tutorial;
+pub mod sandbox;
+pub mod mouse;
+pub mod command;
+pub mod crossterm;
+pub mod app;
+pub mod cli;
@@ -1,19 +1,3 @@
-extern crate serde;
-#[macro_use]
-extern crate serde_derive;
-extern crate serde_json;
-
-// see https://stackoverflow.com/questions/48071513/how-to-use-one-module-from-another-module-in-a-rust-cargo-project/48071730#48071730
-// on combining modules and using sibling modules
-mod ui;
-mod company;
-mod config;
-mod file_io;
-mod account;
-mod transaction;
-mod crossterm;
-mod app;
-
 use std::{
     error::Error,
     io,
@@ -30,17 +14,41 @@ use tui::{
 
 use clap::Parser;
 
-use crate::app::App;
-use crate::crossterm::run_app;
-use crate::config::{Config, Args};
+use money::app::App;
+use money::cli;
+use money::events::Publisher;
+use money::keymap::Keymap;
+use money::theme::Theme;
+use money::state::State;
+use money::tutorial::Tutorial;
+use money::crossterm::run_app;
+use money::config::{Config, Args, Command};
 
 
 fn main() -> Result<(), Box<dyn Error>> {
 
     let args = Args::parse();
-    let config = Config::default()
-        .database(args.database)
-        .tick_rate(args.tick_rate);
+    let config = Config::from_args(&args)?;
+
+    // commands print their result without starting the interface,
+    // except the tutorial which runs in it
+    let is_tutorial = match args.command {
+        Some(Command::Tutorial) => true,
+        Some(command) => return cli::run(command, &config),
+        None => false,
+    };
+
+    // a mistake in the settings is reported before the terminal is taken over
+    let keymap = Keymap::from_settings(&config.keymap)?;
+    let theme = Theme::from_settings(&config.theme, &config.themes)?.effects(config.effects);
+    let state = match &config.state {
+        Some(path) if !is_tutorial => State::load(path)?,
+        _ => State::default(),
+    };
+
+    // the scratch ledger is made once the settings are known to be good,
+    // and removed again when the tutorial is dropped
+    let tutorial = if is_tutorial { Some(Tutorial::create()?) } else { None };
 
     // setup the terminal
     enable_raw_mode()?;
@@ -49,9 +57,22 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // create and start app
-    let app = App::default()
-        .database(config.database.clone());
+    // create and start app. the tutorial leaves the database, the state
+    // file and any subscribers alone
+    let mut app = App::default()
+        .services(config.services.clone())
+        .config(config.clone())
+        .keymap(keymap)
+        .theme(theme);
+    match tutorial {
+        Some(tutorial) => app = app.tutorial(tutorial),
+        None => {
+            app = app.database(config.database.clone()).state(state);
+            if let Some(endpoint) = &config.publish {
+                app = app.publisher(Publisher::bind(endpoint)?);
+            }
+        },
+    }
     let res = run_app(&mut terminal, app, config);
 
     // restore the terminal
@@ -0,0 +1,293 @@
+// the manual, read in the interface with ? or printed with "money help".
+// keys are written as {action} and filled in from the keymap in use,
+// so the manual follows any rebinding
+
+use crate::keymap::{Action, Keymap};
+
+pub struct Topic {
+    pub name: &'static str,
+    // what "money help <name>" looks for
+    pub title: &'static str,
+    pub body: &'static [&'static str],
+}
+
+pub const TOPICS: [Topic; 24] = [
+    Topic {
+        name: "overview",
+        title: "Getting started",
+        body: &[
+            "Money is a double-entry ledger. Every transaction moves an amount from a credit account to a debit account, so the books always balance.",
+            "The database is a JSON file of accounts and transactions, data/db.json unless the settings or --database say otherwise.",
+            "The menu on the left lists what each key opens. Press a key again to close its view.",
+            "Press {edit} to type into the input line, Enter to send it to the open view and Esc to stop typing.",
+            "Press {command} to type a command such as :reg Food instead, see the commands topic.",
+            "Press {help} in any view for help about that view, and {whats_new} to see what has been added lately.",
+            "Every change can be taken back with {undo}.",
+            "Run money tutorial to try the basics on a sample ledger, see the tutorial topic.",
+        ],
+    },
+    Topic {
+        name: "tutorial",
+        title: "Tutorial",
+        body: &[
+            "money tutorial opens Money on a scratch copy of a sample ledger, leaving your own database alone.",
+            "A panel beside the messages walks through adding a transaction, viewing a register, running the balance sheet and undoing an edit.",
+            "Each step is ticked off once Money sees it done. The scratch copy is deleted when Money closes.",
+        ],
+    },
+    Topic {
+        name: "keys",
+        title: "Keys",
+        body: &[],
+        // filled in from the keymap by get_body
+    },
+    Topic {
+        name: "commands",
+        title: "Command line",
+        body: &[
+            "Press {command}, or start the input with a colon, to run a command from any view. Only input starting with a colon is a command.",
+            ":add [YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>] adds a transaction, such as :add 2014-11-28 Expenses:Food Assets 15 \"taco bell\"",
+            ":reg <account or query> lists a register and :bal [--period month|quarter|year] [account or query] lists balances, both in the messages.",
+            ":del <id>, :query <terms>, :undo, :redo and :help <topic> do what their keys do. :commands lists every command.",
+            "Tab completes command names, account paths, payees after payee:, topics and periods. Several matches are listed in the messages.",
+            "On the command line, Up and Down bring back earlier commands, which are kept between runs. :history lists them.",
+        ],
+    },
+    Topic {
+        name: "mouse",
+        title: "Mouse",
+        body: &[
+            "Click a menu entry to open its view, and the input line to start typing.",
+            "Click a row of the chart of accounts, the reconciliation, the manual or what's new to pick it, and again to open or tick it.",
+            "Click a transaction in the register to pick it, then its button to delete it, which asks once more first.",
+            "The wheel moves through lists and scrolls the viewer and the messages.",
+            "Buttons under the viewer answer questions and do what the open view offers.",
+        ],
+    },
+    Topic {
+        name: "balance",
+        title: "Balance sheet",
+        body: &[
+            "Press {balance_sheet} to list the balance of every account, debits first and then credits.",
+            "A query narrows the balances to the transactions it matches, see the query topic.",
+            "From the command line, money query --balance <terms> prints the same.",
+        ],
+    },
+    Topic {
+        name: "register",
+        title: "Register",
+        body: &[
+            "Press {register}, then {edit}, and enter an account such as Expenses:Food to list its transactions with a running total.",
+            "A query works too, such as payee:Landlord and date>=2014-11.",
+            "From the command line, money query <terms> prints the register.",
+        ],
+    },
+    Topic {
+        name: "transactions",
+        title: "Adding, editing and deleting transactions",
+        body: &[
+            "Press {new_transaction}, then {edit}, and enter: [YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>]",
+            "Accounts may be a path such as Expenses:Food, a name or an account_id. The date defaults to today, and a memo of several words may be quoted.",
+            "The same works from any view as a command, :add followed by the same words.",
+            "Transactions can also be added through the ledger service, see the serve topic.",
+            "Press {edit_transaction}, then {edit}, and enter <transaction_id> <field> <value> to change the date, debit, credit, amount, memo or payee.",
+            "Enter <transaction_id> tag <tag> or <transaction_id> untag <tag> to add or remove a tag, and <transaction_id> meta <key> [value] to set metadata. Without a value the key is removed.",
+            "Enter <transaction_id> status <account> [uncleared|pending|cleared] to set the status of a posting. Without a status it moves on from uncleared to pending, cleared and back.",
+            "Press {delete_transaction}, then {edit}, and enter a transaction_id to delete it.",
+            "Editing or deleting a transaction that has been reconciled asks for it a second time, because the bank has already seen it.",
+            "A deleted transaction comes back with {undo}.",
+        ],
+    },
+    Topic {
+        name: "accounts",
+        title: "Chart of accounts",
+        body: &[
+            "Press {charts} to browse the accounts as a tree. Move with {up} and {down}, fold with {left} and unfold with {right}.",
+            "{select} opens the register of the picked account, {add_account} adds a subaccount under it and {rename_account} renames it.",
+            "Press {edit} and enter one of these to change any account by its account_id:",
+            "rename <id> <name>, move <id> <parent_id>, merge <id> <into_id>, close <id>, reopen <id>, cash <id> on|off, category <id> <category> or delete <id>",
+            "Merging moves every transaction into the other account. Closed accounts keep their history but cannot be posted to.",
+        ],
+    },
+    Topic {
+        name: "budgets",
+        title: "Budgets",
+        body: &[
+            "Press {budget} to compare what was spent with the budget of each account.",
+            "Press {edit} in the budget view and enter set <account> <amount> [month|quarter|year] to add a budget or change it, and delete <account> to remove it.",
+            "An account has one budget. The period defaults to month.",
+            "Press {cycle_period} to change the period. Overspent accounts are shown as warnings.",
+        ],
+    },
+    Topic {
+        name: "scheduled",
+        title: "Scheduled transactions",
+        body: &[
+            "Recurrences post a transaction daily, weekly, monthly or yearly, optionally on the last business day of the month.",
+            "Press {edit} in the scheduled view and enter add <frequency> <YYYY-MM-DD> <debit> <credit> <amount> [memo] to add one. Add until:<YYYY-MM-DD> to end it and last-business-day to move it to the last weekday of the month.",
+            "Enter delete <id> to remove one. Transactions it already posted stay in the ledger.",
+            "Press {upcoming} to see what is due and what is coming in the next 60 days, and {approve} to post everything that is due.",
+            "Nothing is posted until it is approved. Money says how many are due when it starts. Nothing is posted while a due recurrence names a closed account.",
+        ],
+    },
+    Topic {
+        name: "reconcile",
+        title: "Reconciling with a bank statement",
+        body: &[
+            "Press {reconcile}, then {edit}, and enter: <account_id> <YYYY-MM-DD> <balance> from the statement.",
+            "Move with {up} and {down} and tick each transaction on the statement with {toggle_cleared}.",
+            "When the difference is zero, {finish_reconciliation} marks the ticked transactions as cleared.",
+        ],
+    },
+    Topic {
+        name: "query",
+        title: "Queries",
+        body: &[
+            "Press {filter}, then {edit}, and enter terms joined by and, such as account:Expenses:Food and amount>50.",
+            "Fields are account, amount, date, memo, payee, tag and meta. Use =, !=, <, >, <=, >= and ~ for contains.",
+            "Add sort:date, sort:-amount or limit:10 to order the register.",
+            "The balance, budget and group views follow the query until it is cleared by entering nothing.",
+        ],
+    },
+    Topic {
+        name: "groups",
+        title: "Payees, tags and metadata",
+        body: &[
+            "Press {groups} to total transactions by payee, by tag and by each metadata key.",
+            "Give transactions a payee, tags or metadata in the database or through the ledger service.",
+        ],
+    },
+    Topic {
+        name: "cashflow",
+        title: "Cash flow statement",
+        body: &[
+            "Press {cash_flow} to see where cash came from and went, by operating, investing and financing.",
+            "Only cash accounts are followed. Mark one from the chart of accounts with: cash <account_id> on",
+            "Press {cycle_period} to change the period, or run money cash-flow from the command line.",
+        ],
+    },
+    Topic {
+        name: "dashboard",
+        title: "Dashboard",
+        body: &[
+            "Press {dashboard} to chart net worth, the balance of one account, and income against expenses by month.",
+            "The account charted is the one picked in the chart of accounts.",
+            "Net worth is the Assets account less Liabilities. Set assets_account and liabilities_account in the settings file to chart other accounts.",
+            "Income and expenses come from the Income and Expenses accounts, or income_account and expenses_account in the settings file.",
+        ],
+    },
+    Topic {
+        name: "expenses",
+        title: "Expense report",
+        body: &[
+            "Press {expense_report} to total the Expenses accounts for the period, and {cycle_period} to change it.",
+            "Press {edit} and enter csv, html or md, and optionally a path, to export it. An export replaces a file of the same name.",
+            "Enter service, and optionally a format, to have the report service build it. {cancel_service} cancels while it works.",
+            "From the command line, money expenses prints it, or exports it with --format.",
+        ],
+    },
+    Topic {
+        name: "sandbox",
+        title: "Sandbox",
+        body: &[
+            "Press {sandbox}, then {edit}, and enter start to try changes on a copy of the ledger. Nothing is saved while it is open.",
+            "Every view works on the copy. The sandbox view compares each balance with the original and counts what changed.",
+            "Enter import <path> to post the transactions of another ledger file whose accounts match by full path. Ones already in the ledger are skipped.",
+            "Enter commit to save the copy as one change, which {undo} takes back, or discard to throw it away.",
+        ],
+    },
+    Topic {
+        name: "undo",
+        title: "Undo and redo",
+        body: &[
+            "Press {undo} to take back the last change to the ledger and {redo} to put it back.",
+            "The history lasts until Money is closed.",
+        ],
+    },
+    Topic {
+        name: "settings",
+        title: "Settings",
+        body: &[
+            "Money reads config.toml from the user config directory, or the file named by --config or MONEY_CONFIG.",
+            "Environment variables override the file and command line flags override both.",
+            "money config show prints the settings in effect. See Settings in the README for every entry.",
+            "Keys are rebound in the [keymap] table, such as undo = \"ctrl-z\". See Keymap in the README.",
+        ],
+    },
+    Topic {
+        name: "themes",
+        title: "Themes and effects",
+        body: &[
+            "Set theme to default, dark, light, high-contrast or monochrome, in the settings file or with --theme.",
+            "high-contrast uses blue against orange rather than green against red, and underlines negative amounts.",
+            "monochrome uses no color, and is picked when NO_COLOR is set. --no-effects turns off blinking and italics.",
+        ],
+    },
+    Topic {
+        name: "services",
+        title: "Report service",
+        body: &[
+            "Start the reference service with: cargo run --bin report_service -- --output reports",
+            "money services lists the services Money knows about. Add one with --service or a [[services]] table.",
+            "A service that does not answer in time is retried, and a spinner shows how long it has been waiting.",
+        ],
+    },
+    Topic {
+        name: "serve",
+        title: "Ledger service and change events",
+        body: &[
+            "money serve answers balances, register, chart, add_transaction and delete_transaction requests over ZMQ.",
+            "With --publish, every change to the ledger is announced with a sequence number so others can follow along.",
+            "Anything that can reach the address can change the ledger. See Ledger service in the README for the requests.",
+        ],
+    },
+    Topic {
+        name: "whats-new",
+        title: "What's new",
+        body: &[
+            "After an update, Money lists the features added since the version you last used.",
+            "Pick one with {up} and {down} and press {select} to open it with a short tutorial. {dismiss} puts the list away.",
+            "Press {whats_new} to see the list again.",
+        ],
+    },
+];
+
+pub fn get_topic(name: &str) -> Option<&'static Topic> {
+    TOPICS.iter().find(|topic| topic.name == name)
+}
+
+pub fn search(terms: &str, keymap: &Keymap) -> Vec<&'static Topic> {
+    // topics holding every word as they read, in the order of the manual
+    let words = terms
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>();
+
+    TOPICS
+        .iter()
+        .filter(|topic| {
+            let text = format!("{} {} {}", topic.name, topic.title, get_body(topic, keymap).join(" ")).to_lowercase();
+            words.iter().all(|word| text.contains(&word[..]))
+        })
+        .collect()
+}
+
+pub fn get_body(topic: &Topic, keymap: &Keymap) -> Vec<String> {
+    if topic.name == "keys" {
+        return Action::all()
+            .map(|action| format!("{:<22} {:<12} {}", action.name(), keymap.get_keys(action), action.description()))
+            .collect();
+    }
+
+    topic.body.iter().map(|line| fill_keys(line, keymap)).collect()
+}
+
+pub fn fill_keys(line: &str, keymap: &Keymap) -> String {
+    // replaces each {action} with the keys bound to it
+    let mut text = line.to_string();
+    for action in Action::all() {
+        text = text.replace(&format!("{{{}}}", action.name()), &keymap.get_keys(action));
+    }
+
+    text
+}
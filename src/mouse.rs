@@ -0,0 +1,292 @@
+// the mouse picks menu entries, rows and buttons, and the wheel scrolls.
+// the interface records where it drew each of them in the Screen every
+// frame, so a click is matched against what is on the screen right now
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tui::layout::Rect;
+use crate::app::{App, Focus, InputMode};
+use crate::keymap::Action;
+
+// Where the last frame drew everything that can be clicked
+#[derive(Debug, Default)]
+pub struct Screen {
+    pub menu: Rect,
+    pub input: Rect,
+    pub viewer: Rect,
+    pub messages: Rect,
+    pub rows: Vec<(u16, Row)>,
+    // the screen line of each row of the viewer that can be picked
+    pub lines: Vec<(usize, Row)>,
+    // rows by their line in the text of the viewer, placed once it is drawn
+    pub buttons: Vec<(Rect, Target)>,
+    pub viewer_lines: u16,
+    // how many lines the viewer has, to stop scrolling past the end
+}
+
+impl Screen {
+
+    pub fn place_lines(&mut self, starts: &[usize], scroll: u16) {
+        // starts holds where each line of the text begins once wrapped.
+        // rows are kept inside the border and below what is scrolled off
+        let top = self.viewer.y as usize + 1;
+        let bottom = (self.viewer.y + self.viewer.height).saturating_sub(1) as usize;
+
+        for (line, row) in std::mem::take(&mut self.lines) {
+            for offset in starts[line]..starts[line + 1] {
+                let y = (top + offset).checked_sub(scroll as usize);
+                if let Some(y) = y.filter(|y| *y >= top && *y < bottom) {
+                    self.rows.push((y as u16, row.clone()));
+                }
+            }
+        }
+    }
+
+    pub fn add_list<F>(&mut self, area: Rect, heights: &[usize], selected: usize, row: F)
+    where F: Fn(usize) -> Row
+    {
+        // a list scrolls just far enough to show the selected item, the
+        // way tui draws a List without an offset of its own
+        let max_height = area.height as usize;
+        let (mut start, mut end, mut height) = (0, 0, 0);
+        for item in heights {
+            if height + item > max_height {
+                break;
+            }
+            height += item;
+            end += 1;
+        }
+        while selected >= end && end < heights.len() {
+            height += heights[end];
+            end += 1;
+            while height > max_height {
+                height -= heights[start];
+                start += 1;
+            }
+        }
+
+        let mut y = area.y;
+        for (index, item) in heights.iter().enumerate().take(end).skip(start) {
+            for _ in 0..*item {
+                self.rows.push((y, row(index)));
+                y += 1;
+            }
+        }
+    }
+}
+
+// A row of a list in the viewer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Row {
+    Account(usize),
+    // its place in the chart of accounts
+    Notice(usize),
+    Topic(usize),
+    Reconcile(usize),
+    Transaction(String),
+    // a transaction_id in the register
+}
+
+// What a button does
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Action(Action),
+    // as if its key was pressed
+    Submit(String),
+    // entered into the focused view as if typed
+    SubmitInput,
+    StopEditing,
+    Delete(String),
+    Cancel,
+    // puts away a question waiting for an answer
+}
+
+pub fn get_buttons(app: &App) -> Vec<(String, Target)> {
+    // the buttons for what the user is in the middle of, the most
+    // pressing first
+    let button = |label: &str, target: Target| (label.to_string(), target);
+
+    if app.quit_warned {
+        return vec![
+            button("Quit and lose the sandbox", Target::Action(Action::Quit)),
+            button("Stay", Target::Cancel),
+        ];
+    }
+    if let Some(id) = &app.pending_delete {
+        return vec![
+            button(&format!("Yes, delete transaction_id {}", id), Target::Delete(id.clone())),
+            button("Keep it", Target::Cancel),
+        ];
+    }
+    if let Some(edit) = &app.pending_edit {
+        return vec![
+            button("Edit it anyway", Target::Submit(edit.clone())),
+            button("Leave it", Target::Cancel),
+        ];
+    }
+    if let InputMode::Editing = app.input_mode {
+        return vec![
+            button("Enter", Target::SubmitInput),
+            button("Stop editing", Target::StopEditing),
+        ];
+    }
+
+    let mut buttons = match app.focus {
+        Focus::Sandbox if app.sandbox.is_some() => vec![
+            button("Commit", Target::Submit("commit".to_string())),
+            button("Discard", Target::Submit("discard".to_string())),
+        ],
+        Focus::Sandbox => vec![button("Start", Target::Submit("start".to_string()))],
+        Focus::Register => match &app.register_selected {
+            Some(id) if app.company.get_transaction_by_id(id).is_some() => {
+                vec![button(&format!("Delete transaction_id {}", id), Target::Delete(id.clone()))]
+            },
+            _ => Vec::new(),
+        },
+        Focus::Reconcile if app.reconciliation.is_some() => vec![
+            button("Tick", Target::Action(Action::ToggleCleared)),
+            button("Finish", Target::Action(Action::FinishReconciliation)),
+        ],
+        Focus::Upcoming => vec![button("Approve due", Target::Action(Action::Approve))],
+        Focus::ExpenseReport => vec![
+            button("Period", Target::Action(Action::CyclePeriod)),
+            button("CSV", Target::Submit("csv".to_string())),
+            button("HTML", Target::Submit("html".to_string())),
+            button("Markdown", Target::Submit("md".to_string())),
+        ],
+        Focus::Budget | Focus::CashFlow => vec![button("Period", Target::Action(Action::CyclePeriod))],
+        Focus::WhatsNew if !app.notices.is_empty() => vec![
+            button("Try it", Target::Action(Action::Select)),
+            button("Dismiss", Target::Action(Action::Dismiss)),
+        ],
+        Focus::Help if app.help_topic.is_some() => vec![button("Topics", Target::Action(Action::Dismiss))],
+        _ => Vec::new(),
+    };
+    if app.service_job.is_some() {
+        buttons.push(button("Cancel service", Target::Action(Action::CancelService)));
+    }
+
+    buttons
+}
+
+pub fn on_mouse(app: &mut App, event: &MouseEvent) -> Option<Action> {
+    // returns the action to perform, if the mouse asked for one
+    let (column, row) = (event.column, event.row);
+
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => click(app, column, row),
+        MouseEventKind::ScrollDown => {
+            scroll(app, column, row, true);
+            None
+        },
+        MouseEventKind::ScrollUp => {
+            scroll(app, column, row, false);
+            None
+        },
+        _ => None,
+    }
+}
+
+fn click(app: &mut App, column: u16, row: u16) -> Option<Action> {
+    let button = app.screen.buttons
+        .iter()
+        .find(|(area, _)| contains(area, column, row))
+        .map(|(_, target)| target.clone());
+    if let Some(target) = button {
+        return press(app, target);
+    }
+
+    if contains(&app.screen.menu, column, row) {
+        // the menu acts as its keys would, which only work outside editing
+        app.stop_editing();
+        let index = (row - app.screen.menu.y) as usize;
+        return app.keymap.get_menu().get(index).map(|(action, ..)| *action);
+    }
+
+    if contains(&app.screen.input, column, row) {
+        return match app.input_mode {
+            InputMode::Normal => Some(Action::Edit),
+            InputMode::Editing => None,
+        };
+    }
+
+    if contains(&app.screen.viewer, column, row) {
+        let picked = app.screen.rows
+            .iter()
+            .find(|(line, _)| *line == row)
+            .map(|(_, picked)| picked.clone());
+        if let Some(picked) = picked {
+            return pick(app, picked);
+        }
+    }
+
+    None
+}
+
+fn pick(app: &mut App, row: Row) -> Option<Action> {
+    // the first click picks a row, and a click on the picked row opens it
+    match row {
+        Row::Account(index) if app.chart_selected == index => return Some(Action::Select),
+        Row::Account(index) => app.chart_selected = index,
+        Row::Notice(index) if app.notice_selected == index => return Some(Action::Select),
+        Row::Notice(index) => app.notice_selected = index,
+        Row::Topic(index) if app.help_selected == index => return Some(Action::Select),
+        Row::Topic(index) => app.help_selected = index,
+        Row::Reconcile(index) => {
+            let reconciliation = app.reconciliation.as_mut()?;
+            if reconciliation.selected == index {
+                return Some(Action::ToggleCleared);
+            }
+            reconciliation.selected = index;
+        },
+        Row::Transaction(id) => app.register_selected = Some(id),
+    }
+
+    None
+}
+
+fn press(app: &mut App, target: Target) -> Option<Action> {
+    match target {
+        Target::Action(action) => return Some(action),
+        Target::Submit(input) => {
+            app.input = input;
+            app.submit_input();
+        },
+        Target::SubmitInput => app.submit_input(),
+        Target::StopEditing => app.stop_editing(),
+        Target::Delete(id) => app.click_delete(&id),
+        Target::Cancel => app.cancel_confirmation(),
+    }
+
+    None
+}
+
+fn scroll(app: &mut App, column: u16, row: u16, down: bool) {
+    // the wheel moves through the lists the keys move through, and
+    // scrolls the rest of the viewer and the messages
+    if contains(&app.screen.viewer, column, row) {
+        match (app.focus, down) {
+            (Focus::Charts, true) => app.chart_select_next(),
+            (Focus::Charts, false) => app.chart_select_previous(),
+            (Focus::WhatsNew, true) => app.notice_select_next(),
+            (Focus::WhatsNew, false) => app.notice_select_previous(),
+            (_, true) => {
+                let last = app.screen.viewer_lines.saturating_sub(1);
+                app.viewer_scroll = (app.viewer_scroll + 1).min(last);
+            },
+            (_, false) => app.viewer_scroll = app.viewer_scroll.saturating_sub(1),
+        }
+    }
+    else if contains(&app.screen.messages, column, row) {
+        if down {
+            let last = app.messages.len().saturating_sub(1);
+            app.messages_scroll = (app.messages_scroll + 1).min(last);
+        }
+        else {
+            app.messages_scroll = app.messages_scroll.saturating_sub(1);
+        }
+    }
+}
+
+fn contains(area: &Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
+}
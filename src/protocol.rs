@@ -0,0 +1,258 @@
+// the messages exchanged with the report service.
+// see "Report service protocol" in the README for the full spec
+
+use chrono::prelude::*;
+use serde_json::Value;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use crate::file_io;
+
+// the protocol version this client speaks
+pub const VERSION: u32 = 1;
+
+// the formats an inline report may be saved as. the format becomes
+// the file extension, so anything else is refused
+pub const FORMATS: [&str; 5] = ["txt", "csv", "json", "html", "md"];
+
+// What the report should cover and how it should be delivered
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportParams {
+    pub period: String,
+    #[serde(default)]
+    pub start: Option<NaiveDate>,
+    #[serde(default)]
+    pub end: Option<NaiveDate>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    // account ids to report on, everything when empty
+    pub format: String,
+    // "csv", "html" or "md"
+}
+
+impl Default for ReportParams {
+    fn default() -> ReportParams {
+        ReportParams {
+            period: "month".to_string(),
+            start: None,
+            end: None,
+            accounts: Vec::new(),
+            format: "html".to_string(),
+        }
+    }
+}
+
+// A Request asks the service for one piece of work
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Request {
+    pub version: u32,
+    pub r#type: String,
+    // only "expense_report" so far
+    #[serde(default)]
+    pub params: ReportParams,
+    pub database: Value,
+    // the company as stored in the database file
+    #[serde(skip)]
+    pub raw_database: Option<String>,
+    // the database text as it was read, sent unchanged to services without the protocol
+}
+
+impl Request {
+
+    pub fn expense_report(database: &str, params: ReportParams) -> Result<Self, Box<dyn Error>> {
+        Ok(Request {
+            version: VERSION,
+            r#type: "expense_report".to_string(),
+            params,
+            database: serde_json::from_str(database)?,
+            raw_database: Some(database.to_string()),
+        })
+    }
+
+    pub fn parse(payload: &str) -> Result<Self, Box<dyn Error>> {
+        // an envelope carries a version, anything else is a bare database
+        // from a client that predates the protocol
+        let value: Value = serde_json::from_str(payload)?;
+
+        if value.get("version").is_some() {
+            return Ok(serde_json::from_value(value)?);
+        }
+
+        Ok(Request {
+            version: 0,
+            r#type: "expense_report".to_string(),
+            params: ReportParams::default(),
+            database: value,
+            raw_database: Some(payload.to_string()),
+        })
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.version == 0
+    }
+
+    pub fn to_payload(&self, legacy: bool) -> Result<String, Box<dyn Error>> {
+        // services without the protocol only understand the database itself
+        if legacy {
+            return match &self.raw_database {
+                Some(raw) => Ok(raw.clone()),
+                None => Ok(serde_json::to_string(&self.database)?),
+            };
+        }
+
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+// A LedgerRequest reads or changes the ledger held by "money serve".
+// the params depend on the type, see the README
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LedgerRequest {
+    pub version: u32,
+    pub r#type: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ErrorDetail {
+    pub code: String,
+    pub message: String,
+}
+
+// What the service produced. a path to a file it wrote, the content inline, or both
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Artifact {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub format: String,
+}
+
+impl Artifact {
+
+    pub fn save(&self, directory: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        // a written file is used where it is, inline content is saved beside the database
+        if let Some(path) = &self.path {
+            return Ok(to_local_path(path));
+        }
+
+        let content = self.content.as_ref().ok_or("the service returned no report")?;
+        let extension = if self.format.is_empty() { "txt" } else { &self.format[..] };
+        if !FORMATS.contains(&extension) {
+            return Err(format!("the service returned a report in an unknown format {:?}", self.format).into());
+        }
+        let path = directory.join(format!("expense-report.{}", extension));
+
+        let location = path.to_str().ok_or("report path is not valid unicode")?;
+        file_io::truncate(location)?;
+        file_io::write(location, content)?;
+
+        Ok(path)
+    }
+}
+
+// A Response answers a Request. status is "ok" or "error"
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Response {
+    pub version: u32,
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<ErrorDetail>,
+    #[serde(default)]
+    pub artifact: Option<Artifact>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    // what a ledger request asked for
+}
+
+impl Response {
+
+    pub fn ok(artifact: Artifact) -> Self {
+        Response {
+            version: VERSION,
+            status: "ok".to_string(),
+            error: None,
+            artifact: Some(artifact),
+            data: None,
+        }
+    }
+
+    pub fn data(data: Value) -> Self {
+        Response {
+            version: VERSION,
+            status: "ok".to_string(),
+            error: None,
+            artifact: None,
+            data: Some(data),
+        }
+    }
+
+    pub fn error(code: &str, message: &str) -> Self {
+        Response {
+            version: VERSION,
+            status: "error".to_string(),
+            error: Some(ErrorDetail {
+                code: code.to_string(),
+                message: message.to_string(),
+            }),
+            artifact: None,
+            data: None,
+        }
+    }
+
+    pub fn parse(reply: &str) -> Result<Self, Box<dyn Error>> {
+        // the original service answers with nothing but the report path
+        if let Ok(response) = serde_json::from_str::<Response>(reply) {
+            if response.version > VERSION {
+                return Err(format!("the service speaks protocol version {}, newer than {}", response.version, VERSION).into());
+            }
+            return Ok(response);
+        }
+
+        let path = reply.trim();
+        if path.is_empty() || path.starts_with('{') {
+            return Err("the service reply could not be understood".into());
+        }
+
+        Ok(Response {
+            version: 0,
+            status: "ok".to_string(),
+            error: None,
+            artifact: Some(Artifact {
+                path: Some(path.to_string()),
+                content: None,
+                format: String::new(),
+            }),
+            data: None,
+        })
+    }
+
+    pub fn into_artifact(self) -> Result<Artifact, Box<dyn Error>> {
+        if self.status != "ok" {
+            let message = match self.error {
+                Some(error) => format!("{}: {}", error.code, error.message),
+                None => format!("status {}", self.status),
+            };
+            return Err(message.into());
+        }
+
+        self.artifact.ok_or_else(|| "the service returned no report".into())
+    }
+}
+
+fn to_local_path(path: &str) -> PathBuf {
+    // the service may run on Windows, so split its path on either separator
+    path.split(['\\', '/'])
+        .enumerate()
+        .fold(PathBuf::new(), |mut result, (idx, part)| {
+            if idx == 0 && part.is_empty() {
+                result.push("/");
+            }
+            else if !part.is_empty() {
+                result.push(part);
+            }
+            result
+        })
+}
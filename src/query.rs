@@ -0,0 +1,371 @@
+use chrono::prelude::*;
+use std::borrow::Cow;
+use std::error::Error;
+use crate::company::Company;
+use crate::filter::{Comparison, Condition, Filter};
+use crate::recurrence;
+use crate::transaction::Transaction;
+
+const ALL: [Comparison; 7] = [
+    Comparison::Equal,
+    Comparison::NotEqual,
+    Comparison::Less,
+    Comparison::LessOrEqual,
+    Comparison::Greater,
+    Comparison::GreaterOrEqual,
+    Comparison::Contains,
+];
+const ORDERING: [Comparison; 6] = [
+    Comparison::Equal,
+    Comparison::NotEqual,
+    Comparison::Less,
+    Comparison::LessOrEqual,
+    Comparison::Greater,
+    Comparison::GreaterOrEqual,
+];
+const MATCHING: [Comparison; 3] = [Comparison::Equal, Comparison::NotEqual, Comparison::Contains];
+
+// A Query is a Filter plus the order and number of transactions to return.
+// it is written as terms joined by "and", for example
+// account:Expenses:Food and amount>50 and date>=2014-11 and memo~restaurant sort:-amount limit:10
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub filter: Filter,
+    pub sort: Option<(String, bool)>,
+    // the field to sort on and whether to sort descending
+    pub limit: Option<usize>,
+}
+
+impl Query {
+
+    pub fn new() -> Self {
+        Query::default()
+    }
+
+    pub fn parse(company: &Company, input: &str) -> Result<Self, Box<dyn Error>> {
+        let mut query = Query::new();
+
+        for token in tokenize(input)? {
+            if token.is_empty() || token.eq_ignore_ascii_case("and") {
+                continue;
+            }
+
+            if let Some(tag) = token.strip_prefix('#') {
+                query.filter.add_tag(tag);
+                continue;
+            }
+
+            let (field, symbol, comparison, value) = split_term(&token)?;
+            let field = field.to_lowercase();
+
+            // fields that are not ordered only take some of the comparisons
+            let allowed = match &field[..] {
+                "account" | "tag" | "meta" => &MATCHING[..],
+                "amount" | "date" => &ORDERING[..],
+                "sort" | "limit" => &[Comparison::Equal][..],
+                _ => &ALL[..],
+            };
+            if !allowed.contains(&comparison) {
+                return Err(format!("cannot compare {} with {}", field, symbol).into());
+            }
+
+            match &field[..] {
+                "account" => {
+                    let condition = parse_account(company, comparison, &value)?;
+                    query.filter.add_condition(condition);
+                },
+                "amount" => {
+                    let amount = value.parse::<f64>()
+                        .map_err(|_| format!("{} is not an amount", value))?;
+                    query.filter.add_condition(Condition::Amount(comparison, amount));
+                },
+                "date" => {
+                    let (first, last) = parse_date(&value)?;
+                    query.filter.add_condition(Condition::Date(comparison, first, last));
+                },
+                "memo" => {
+                    // a colon searches within the memo like ~ does
+                    let comparison = if symbol == ":" {
+                        Comparison::Contains
+                    }
+                    else {
+                        comparison
+                    };
+                    query.filter.add_condition(Condition::Memo(comparison, value));
+                },
+                "payee" => {
+                    query.filter.add_condition(Condition::Payee(comparison, value));
+                },
+                "tag" => {
+                    let tag = value.trim_start_matches('#').to_string();
+                    query.filter.add_condition(Condition::Tag(comparison, tag));
+                },
+                "meta" => {
+                    let (key, value) = value.split_once('=').unwrap_or((&value[..], ""));
+                    query.filter.add_condition(Condition::Metadata(comparison, key.to_string(), value.to_string()));
+                },
+                "sort" => {
+                    let (name, descending) = match value.strip_prefix('-') {
+                        Some(name) => (name.to_lowercase(), true),
+                        None => (value.to_lowercase(), false),
+                    };
+                    if !matches!(&name[..], "date" | "amount" | "memo" | "payee" | "id") {
+                        return Err(format!("cannot sort by {}", name).into());
+                    }
+                    query.sort = Some((name, descending));
+                },
+                "limit" => {
+                    let limit = value.parse::<usize>()
+                        .map_err(|_| format!("{} is not a limit", value))?;
+                    query.limit = Some(limit);
+                },
+                _ => return Err(format!("unknown field {}", field).into()),
+            }
+        }
+
+        Ok(query)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filter.is_empty() && self.sort.is_none() && self.limit.is_none()
+    }
+
+    pub fn run<'a>(&self, company: &'a Company) -> Vec<&'a Transaction> {
+        // matching transactions in date order unless another sort was asked for
+        let mut transactions = company.transactions
+            .iter()
+            .filter(|transaction| self.filter.matches(transaction))
+            .collect::<Vec<&Transaction>>();
+
+        transactions.sort_by(|a, b| a.date.cmp(&b.date).then(a.cmp(b)));
+
+        if let Some((field, descending)) = &self.sort {
+            transactions.sort_by(|a, b| {
+                let ordering = match &field[..] {
+                    "amount" => {
+                        let first = a.amount.parse::<f64>().unwrap_or(0.0);
+                        let second = b.amount.parse::<f64>().unwrap_or(0.0);
+                        first.partial_cmp(&second).unwrap_or(std::cmp::Ordering::Equal)
+                    },
+                    "memo" => a.memo.to_lowercase().cmp(&b.memo.to_lowercase()),
+                    "payee" => a.payee.to_lowercase().cmp(&b.payee.to_lowercase()),
+                    "id" => a.cmp(b),
+                    _ => a.date.cmp(&b.date),
+                };
+                if *descending { ordering.reverse() } else { ordering }
+            });
+        }
+
+        if let Some(limit) = self.limit {
+            transactions.truncate(limit);
+        }
+
+        transactions
+    }
+
+    pub fn filtered<'a>(&self, company: &'a Company) -> Cow<'a, Company> {
+        // the company holding only the transactions the query returns, so
+        // the sort and limit narrow the balances as well as the filter
+        if self.sort.is_none() && self.limit.is_none() {
+            return company.filtered(&self.filter);
+        }
+
+        Cow::Owned(Company {
+            accounts: company.accounts.clone(),
+            transactions: self.run(company).into_iter().cloned().collect(),
+            budgets: company.budgets.clone(),
+            recurrences: company.recurrences.clone(),
+        })
+    }
+
+    pub fn get_balances(&self, company: &Company, start: Option<NaiveDate>, end: Option<NaiveDate>) -> Vec<(String, f64)> {
+        // the balances of the transactions the query returns, rolled up
+        // through the account tree like the balance sheet. names are
+        // indented by their depth and empty accounts are left out
+        let company = self.filtered(company);
+
+        company
+            .get_account_tree()
+            .into_iter()
+            .map(|(account, depth)| (
+                format!("{}{}", "  ".repeat(depth), account.name),
+                company.get_rollup_balance_between(&account.id, start, end),
+            ))
+            .filter(|(_, balance)| *balance != 0.0)
+            .collect()
+    }
+
+    pub fn run_register<'a>(&self, company: &'a Company) -> Vec<(&'a Transaction, f64, f64)> {
+        // each match with its amount and the running total. both follow
+        // the sign of the account when one was named
+        let account = self.get_account().and_then(|id| company.get_acccount_by_id(id));
+
+        let mut total = 0.0;
+        self.run(company)
+            .into_iter()
+            .map(|transaction| {
+                let amount = match account {
+                    Some(account) => company
+                        .get_descendant_ids(&account.id)
+                        .iter()
+                        .filter_map(|id| company.get_acccount_by_id(id))
+                        .map(|subaccount| transaction.get_signed_amount(subaccount))
+                        .sum(),
+                    None => transaction.amount.parse::<f64>().unwrap_or(0.0),
+                };
+                total += amount;
+                (transaction, amount, total)
+            })
+            .collect()
+    }
+
+    pub fn get_account(&self) -> Option<&str> {
+        // the account named by the first account term, if any
+        self.filter.conditions.iter().find_map(|condition| match condition {
+            Condition::Account(Comparison::Equal, _, ids) => ids.first().map(|id| &id[..]),
+            _ => None,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = self.filter.describe();
+
+        if let Some((field, descending)) = &self.sort {
+            text = format!("{} sort:{}{}", text, if *descending { "-" } else { "" }, field);
+        }
+        if let Some(limit) = self.limit {
+            text = format!("{} limit:{}", text, limit);
+        }
+
+        text.trim().to_string()
+    }
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    // splits on whitespace while keeping "quoted text" together.
+    // the quotes themselves are dropped, and "" is an empty token
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    tokens.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            },
+            c => {
+                current.push(c);
+                started = true;
+            },
+        }
+    }
+
+    if quoted {
+        return Err("missing closing quote".into());
+    }
+    if started {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+pub fn split_words(input: &str) -> Vec<String> {
+    // tokenize for the command line, where a quote left open runs to the end
+    tokenize(input)
+        .or_else(|_| tokenize(&format!("{}\"", input)))
+        .unwrap_or_default()
+}
+
+fn split_term(token: &str) -> Result<(String, &'static str, Comparison, String), Box<dyn Error>> {
+    // a term is a field name, a comparison and a value
+    let idx = token
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .ok_or_else(|| format!("{} is missing a comparison", token))?;
+    let (field, rest) = token.split_at(idx);
+
+    let operators = [
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        ("!=", Comparison::NotEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+        (":", Comparison::Equal),
+        ("~", Comparison::Contains),
+    ];
+
+    for (symbol, comparison) in operators {
+        if let Some(value) = rest.strip_prefix(symbol) {
+            return Ok((field.to_string(), symbol, comparison, value.to_string()));
+        }
+    }
+
+    Err(format!("{} is missing a comparison", token).into())
+}
+
+fn parse_account(company: &Company, comparison: Comparison, value: &str) -> Result<Condition, Box<dyn Error>> {
+    // ~ matches every account whose path contains the text,
+    // anything else has to name one account by path, name or id
+    let accounts = match comparison {
+        Comparison::Contains => company.accounts
+            .iter()
+            .filter(|account| {
+                company.get_account_path(&account.id).to_lowercase().contains(&value.to_lowercase())
+            })
+            .collect(),
+        _ => company.find_account_by_path(value).into_iter().collect::<Vec<_>>(),
+    };
+
+    if accounts.is_empty() {
+        return Err(format!("no account matches {}", value).into());
+    }
+
+    let mut ids = Vec::<String>::new();
+    for account in accounts {
+        for id in company.get_descendant_ids(&account.id) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    Ok(Condition::Account(comparison, value.to_string(), ids))
+}
+
+pub fn parse_date(value: &str) -> Result<(NaiveDate, NaiveDate), Box<dyn Error>> {
+    // accepts a year, a year and month, or a full date and
+    // returns the first and last day that it covers
+    let parts = value
+        .split('-')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .map_err(|_| format!("{} is not a date", value))?;
+
+    let bad_date = || format!("{} is not a date", value);
+
+    match parts[..] {
+        [year] => {
+            let first = NaiveDate::from_ymd_opt(year as i32, 1, 1).ok_or_else(bad_date)?;
+            Ok((first, NaiveDate::from_ymd(year as i32, 12, 31)))
+        },
+        [year, month] => {
+            let first = NaiveDate::from_ymd_opt(year as i32, month, 1).ok_or_else(bad_date)?;
+            let last = NaiveDate::from_ymd(year as i32, month, recurrence::days_in_month(year as i32, month));
+            Ok((first, last))
+        },
+        [year, month, day] => {
+            let date = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(bad_date)?;
+            Ok((date, date))
+        },
+        _ => Err(bad_date().into()),
+    }
+}
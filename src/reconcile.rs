@@ -0,0 +1,162 @@
+use chrono::prelude::*;
+use std::error::Error;
+use crate::company::Company;
+use crate::transaction::Transaction;
+
+// A Reconciliation matches the postings of one Account against a bank statement
+#[derive(Debug, Clone)]
+pub struct Reconciliation {
+    pub account: String,
+    pub statement_date: NaiveDate,
+    pub statement_balance: f64,
+    pub ticked: Vec<String>,
+    pub selected: usize,
+}
+
+impl Reconciliation {
+
+    pub fn new(account: &str, statement_date: NaiveDate, statement_balance: f64) -> Self {
+        Reconciliation {
+            account: account.to_string(),
+            statement_date,
+            statement_balance,
+            ticked: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn start(company: &Company, account: &str, statement_date: NaiveDate, statement_balance: f64) -> Result<Self, Box<dyn Error>> {
+        // begins reconciling with the postings the bank already cleared ticked
+        if company.get_acccount_by_id(account).is_none() {
+            return Err(format!("Account_id {} cannot be found", account).into());
+        }
+
+        let mut reconciliation = Reconciliation::new(account, statement_date, statement_balance);
+        reconciliation.ticked = reconciliation
+            .get_candidates(company)
+            .iter()
+            .filter(|transaction| transaction.get_status(account) == "cleared")
+            .map(|transaction| transaction.id.clone())
+            .collect();
+
+        Ok(reconciliation)
+    }
+
+    pub fn parse(company: &Company, input: &str) -> Result<Self, Box<dyn Error>> {
+        // reads "<account_id> <statement date> <statement balance>"
+        let parts = input.split_whitespace().collect::<Vec<&str>>();
+
+        if parts.len() != 3 {
+            return Err("expected: <account_id> <YYYY-MM-DD> <balance>".into());
+        }
+
+        let date = NaiveDate::parse_from_str(parts[1], "%Y-%m-%d")?;
+        let balance = parts[2].parse::<f64>()?;
+
+        Reconciliation::start(company, parts[0], date, balance)
+    }
+
+    pub fn get_candidates<'a>(&self, company: &'a Company) -> Vec<&'a Transaction> {
+        // postings up to the statement date that are not yet reconciled
+        let mut candidates = company
+            .get_transactions_by_account(&self.account)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|transaction| transaction.date <= self.statement_date)
+            .filter(|transaction| transaction.get_status(&self.account) != "reconciled")
+            .collect::<Vec<&Transaction>>();
+        candidates.sort_by(|a, b| a.date.cmp(&b.date).then(a.cmp(b)));
+
+        candidates
+    }
+
+    pub fn get_opening_balance(&self, company: &Company) -> f64 {
+        // the balance already agreed with the bank in earlier reconciliations
+        let account = match company.get_acccount_by_id(&self.account) {
+            Some(account) => account,
+            None => return 0.0,
+        };
+
+        company
+            .get_transactions_by_account(&self.account)
+            .unwrap_or_default()
+            .iter()
+            .filter(|transaction| transaction.get_status(&self.account) == "reconciled")
+            .map(|transaction| transaction.get_signed_amount(account))
+            .sum()
+    }
+
+    pub fn get_cleared_balance(&self, company: &Company) -> f64 {
+        // the opening balance plus every ticked posting
+        let account = match company.get_acccount_by_id(&self.account) {
+            Some(account) => account,
+            None => return 0.0,
+        };
+
+        let ticked: f64 = self
+            .get_candidates(company)
+            .iter()
+            .filter(|transaction| self.ticked.contains(&transaction.id))
+            .map(|transaction| transaction.get_signed_amount(account))
+            .sum();
+
+        self.get_opening_balance(company) + ticked
+    }
+
+    pub fn get_difference(&self, company: &Company) -> f64 {
+        self.statement_balance - self.get_cleared_balance(company)
+    }
+
+    pub fn is_balanced(&self, company: &Company) -> bool {
+        // amounts are stored as decimal strings so allow for float error
+        self.get_difference(company).abs() < 0.005
+    }
+
+    pub fn toggle(&mut self, transaction_id: &str) {
+        if self.ticked.iter().any(|id| id == transaction_id) {
+            self.ticked.retain(|id| id != transaction_id);
+        }
+        else {
+            self.ticked.push(transaction_id.to_string());
+        }
+    }
+
+    pub fn toggle_selected(&mut self, company: &Company) {
+        let id = self
+            .get_candidates(company)
+            .get(self.selected)
+            .map(|transaction| transaction.id.clone());
+
+        if let Some(id) = id {
+            self.toggle(&id);
+        }
+    }
+
+    pub fn select_next(&mut self, company: &Company) {
+        let count = self.get_candidates(company).len();
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn finish(&self, company: &mut Company) -> Result<usize, Box<dyn Error>> {
+        // locks the ticked postings as reconciled once the difference is zero
+        if !self.is_balanced(company) {
+            return Err(format!("difference of {:.2} remains", self.get_difference(company)).into());
+        }
+
+        let mut count = 0;
+        for transaction in company.transactions.iter_mut() {
+            if self.ticked.contains(&transaction.id) {
+                transaction.set_status(&self.account, "reconciled");
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+}
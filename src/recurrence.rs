@@ -0,0 +1,192 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use std::cmp::Ordering;
+use crate::company::Company;
+use crate::transaction::Transaction;
+
+// Recurrences are templates that produce a Transaction on a schedule
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Recurrence {
+    pub id: String,
+    pub frequency: String,
+    // frequency is one of "daily", "weekly", "monthly" or "yearly"
+    pub start: NaiveDate,
+    #[serde(default)]
+    pub end: Option<NaiveDate>,
+    #[serde(default)]
+    pub last_business_day: bool,
+    // moves monthly and yearly occurrences to the last weekday of their month
+    #[serde(default)]
+    pub occurrences: usize,
+    // how many transactions have already been generated
+    pub credit: String,
+    pub debit: String,
+    pub amount: String,
+    pub memo: String
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Recurrence::new()
+    }
+}
+
+impl Recurrence {
+
+    pub fn new() -> Self {
+        Recurrence {
+            id: "".to_string(),
+            frequency: "monthly".to_string(),
+            start: Utc::today().naive_utc(),
+            end: None,
+            last_business_day: false,
+            occurrences: 0,
+            credit: "".to_string(),
+            debit: "".to_string(),
+            amount: "".to_string(),
+            memo: "".to_string()
+        }
+    }
+
+    pub fn cmp_id(&self, another: &Recurrence) -> Ordering {
+
+        let first = self.id.parse::<usize>().expect("Bad id");
+        let second = another.id.parse::<usize>().expect("Bad id");
+
+        first.cmp(&second)
+    }
+
+    pub fn set_id_in_company(&mut self, company: &mut Company) -> &mut Self {
+        // set the id to be the next highest one in the company
+        let current_max = company.max_id_recurrence().unwrap_or(0);
+        self.id = (current_max + 1).to_string();
+
+        self
+    }
+
+    pub fn set_frequency(&mut self, frequency: &str) -> &mut Self {
+        // only accepts the known frequencies
+        match frequency {
+            "daily" | "weekly" | "monthly" | "yearly" => self.frequency = frequency.to_string(),
+            _ => {}
+        };
+
+        self
+    }
+
+    pub fn set_start(&mut self, start: NaiveDate) -> &mut Self {
+        self.start = start;
+
+        self
+    }
+
+    pub fn set_end(&mut self, end: Option<NaiveDate>) -> &mut Self {
+        self.end = end;
+
+        self
+    }
+
+    pub fn set_last_business_day(&mut self, last_business_day: bool) -> &mut Self {
+        self.last_business_day = last_business_day;
+
+        self
+    }
+
+    pub fn set_template(&mut self, debit: &str, credit: &str, amount: &str, memo: &str) -> &mut Self {
+        // sets the fields copied into every generated transaction
+        self.debit = debit.to_string();
+        self.credit = credit.to_string();
+        self.amount = amount.to_string();
+        self.memo = memo.to_string();
+
+        self
+    }
+
+    pub fn get_occurrence(&self, n: usize) -> Option<NaiveDate> {
+        // the date of the nth occurrence counting from zero at start.
+        // None once the schedule has passed its end date
+        let date = match &self.frequency[..] {
+            "daily" => self.start + Duration::days(n as i64),
+            "weekly" => self.start + Duration::weeks(n as i64),
+            "yearly" => add_months(self.start, n as u32 * 12),
+            _ => add_months(self.start, n as u32),
+        };
+
+        let date = match &self.frequency[..] {
+            "monthly" | "yearly" if self.last_business_day => last_business_day(date.year(), date.month()),
+            _ => date,
+        };
+
+        match self.end {
+            Some(end) if date > end => None,
+            _ => Some(date),
+        }
+    }
+
+    pub fn get_next_date(&self) -> Option<NaiveDate> {
+        // the first occurrence that has not been generated yet
+        self.get_occurrence(self.occurrences)
+    }
+
+    pub fn get_dates_until(&self, until: NaiveDate) -> Vec<NaiveDate> {
+        // every occurrence not yet generated that falls on or before until
+        let mut dates = Vec::new();
+        let mut n = self.occurrences;
+
+        while let Some(date) = self.get_occurrence(n) {
+            if date > until {
+                break;
+            }
+            dates.push(date);
+            n += 1;
+        }
+
+        dates
+    }
+
+    pub fn make_transaction(&self, date: NaiveDate) -> Transaction {
+        // builds the transaction for one occurrence. the id is left for
+        // the company to assign when it is posted
+        let mut transaction = Transaction::new();
+        transaction
+            .set_debit(&self.debit)
+            .set_credit(&self.credit)
+            .set_amount(&self.amount)
+            .set_memo(&self.memo);
+        transaction.date = date;
+
+        transaction
+    }
+}
+
+pub fn add_months(date: NaiveDate, months: u32) -> NaiveDate {
+    // keeps the day of the month, clamping to the end of shorter months
+    let total = date.month0() + months;
+    let year = date.year() + (total / 12) as i32;
+    let month = total % 12 + 1;
+    let day = date.day().min(days_in_month(year, month));
+
+    NaiveDate::from_ymd(year, month, day)
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        NaiveDate::from_ymd(year + 1, 1, 1)
+    }
+    else {
+        NaiveDate::from_ymd(year, month + 1, 1)
+    };
+
+    next.pred().day()
+}
+
+pub fn last_business_day(year: i32, month: u32) -> NaiveDate {
+    // the last day of the month that is not a Saturday or Sunday
+    let mut date = NaiveDate::from_ymd(year, month, days_in_month(year, month));
+
+    while date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun {
+        date = date.pred();
+    }
+
+    date
+}
@@ -0,0 +1,179 @@
+use chrono::prelude::*;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use crate::company::Company;
+use crate::config::Config;
+use crate::file_io;
+
+// the formats an expense report can be exported to
+pub const FORMATS: [&str; 3] = ["csv", "html", "md"];
+
+// One Expenses subaccount and what was spent in it over the period
+#[derive(Debug, Clone)]
+pub struct ExpenseLine {
+    pub account: String,
+    pub name: String,
+    pub depth: usize,
+    pub amount: f64,
+}
+
+// An ExpenseReport totals the Expenses subaccounts for a period.
+// it is worked out from the ledger so no report service is needed
+#[derive(Debug, Clone)]
+pub struct ExpenseReport {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub total: f64,
+    pub lines: Vec<ExpenseLine>,
+}
+
+impl ExpenseReport {
+
+    pub fn generate(company: &Company, start: NaiveDate, end: NaiveDate) -> Result<Self, Box<dyn Error>> {
+        let expenses = company
+            .find_account_by_path("Expenses")
+            .ok_or("there is no Expenses account")?;
+
+        let total = company.get_rollup_balance_between(&expenses.id, Some(start), Some(end));
+        let ids = company.get_descendant_ids(&expenses.id);
+
+        // subaccounts in tree order, indented from the Expenses account
+        let lines = company
+            .get_account_tree()
+            .into_iter()
+            .filter(|(account, _)| account.id != expenses.id && ids.contains(&account.id))
+            .map(|(account, depth)| ExpenseLine {
+                account: company.get_account_path(&account.id),
+                name: account.name.clone(),
+                depth: depth.saturating_sub(1),
+                amount: company.get_rollup_balance_between(&account.id, Some(start), Some(end)),
+            })
+            .filter(|line| line.amount != 0.0)
+            .collect();
+
+        Ok(ExpenseReport { start, end, total, lines })
+    }
+
+    pub fn get_percent(&self, line: &ExpenseLine) -> f64 {
+        if self.total == 0.0 {
+            return 0.0;
+        }
+
+        line.amount / self.total * 100.0
+    }
+
+    pub fn to_lines(&self, bar_width: usize, config: &Config) -> Vec<String> {
+        // the report as plain text with a bar for each account's share
+        let mut text = Vec::new();
+
+        text.push(format!("Expense Report, {} to {}", config.format_date(self.start), config.format_date(self.end)));
+        text.push("".to_string());
+
+        for line in &self.lines {
+            let percent = self.get_percent(line);
+            let bar = "#".repeat((percent / 100.0 * bar_width as f64).round().max(0.0) as usize);
+            let name = format!("{}{}", "  ".repeat(line.depth), line.name);
+            text.push(format!("{:<28}{:>12}{:>8.1}% {}", name, config.format_amount(line.amount), percent, bar));
+        }
+
+        if self.lines.is_empty() {
+            text.push("No expenses in this period".to_string());
+        }
+
+        text.push("".to_string());
+        text.push(format!("{:<28}{:>12}", "Total expenses", config.format_amount(self.total)));
+
+        text
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("account,amount,percent\n");
+
+        for line in &self.lines {
+            csv.push_str(&format!("{},{:.2},{:.1}\n", csv_field(&line.account), line.amount, self.get_percent(line)));
+        }
+        csv.push_str(&format!("Total,{:.2},100.0\n", self.total));
+
+        csv
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Expense Report, {} to {}\n\n", self.start, self.end);
+
+        markdown.push_str("| Account | Amount | Percent |\n");
+        markdown.push_str("|---|---:|---:|\n");
+        for line in &self.lines {
+            markdown.push_str(&format!(
+                "| {} | {:.2} | {:.1}% |\n",
+                line.account.replace('|', "\\|"), line.amount, self.get_percent(line)
+            ));
+        }
+        markdown.push_str(&format!("| **Total** | **{:.2}** | |\n", self.total));
+
+        markdown
+    }
+
+    pub fn to_html(&self) -> String {
+        // a standalone page with the bars drawn as widths
+        let title = format!("Expense Report, {} to {}", self.start, self.end);
+        let mut html = String::new();
+
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", title));
+        html.push_str("<style>td { padding: 2px 8px; } .bar { background: #c0392b; height: 1em; }</style>\n");
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n<table>\n", title));
+        html.push_str("<tr><th>Account</th><th>Amount</th><th>Percent</th><th></th></tr>\n");
+
+        for line in &self.lines {
+            let percent = self.get_percent(line);
+            html.push_str(&format!(
+                "<tr><td>{}</td><td align=\"right\">{:.2}</td><td align=\"right\">{:.1}%</td><td><div class=\"bar\" style=\"width: {:.0}px\"></div></td></tr>\n",
+                html_escape(&line.account), line.amount, percent, percent.max(0.0) * 3.0
+            ));
+        }
+
+        html.push_str(&format!("<tr><th>Total</th><th align=\"right\">{:.2}</th><th></th><th></th></tr>\n", self.total));
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+
+    pub fn export(&self, format: &str, path: Option<&Path>, directory: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        // writes the report in the given format. without a path the file is
+        // named after the period and put in the directory
+        let contents = match format {
+            "csv" => self.to_csv(),
+            "html" => self.to_html(),
+            "md" | "markdown" => self.to_markdown(),
+            _ => return Err(format!("cannot export to {}, use one of {}", format, FORMATS.join(", ")).into()),
+        };
+
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let extension = if format == "markdown" { "md" } else { format };
+                directory.join(format!("expense-report-{}-{}.{}", self.start, self.end, extension))
+            },
+        };
+
+        let location = path.to_str().ok_or("report path is not valid unicode")?;
+        file_io::truncate(location)?;
+        file_io::write(location, contents.trim_end())?;
+
+        Ok(path)
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    }
+    else {
+        text.to_string()
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
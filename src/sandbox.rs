@@ -0,0 +1,168 @@
+// the sandbox forks the ledger so changes can be tried out and compared
+// against the original before they are kept or thrown away.
+// see "Sandbox" in the README
+
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+use serde_json::Value;
+use crate::company::Company;
+use crate::transaction::Transaction;
+use crate::events::{self, Publisher};
+use crate::history::History;
+
+// Everything set aside while the sandbox is open. the app works on a copy
+// of the company, with a history of its own, and saves and publishes nothing
+pub struct Sandbox {
+    pub original: Company,
+    pub history: History,
+    // the undo history of the real ledger
+    pub publisher: Option<Publisher>,
+    pub summary: Option<Summary>,
+    // None until the sandbox view is drawn after a change
+}
+
+// What the sandbox view shows, worked out once per change rather than
+// on every frame
+pub struct Summary {
+    pub counts: BTreeMap<String, usize>,
+    pub changes: Vec<BalanceChange>,
+}
+
+// One account as it stands in the original and in the sandbox
+pub struct BalanceChange {
+    pub path: String,
+    pub before: Option<f64>,
+    // None for an account added in the sandbox
+    pub after: f64,
+}
+
+impl BalanceChange {
+
+    pub fn get_difference(&self) -> f64 {
+        self.after - self.before.unwrap_or(0.0)
+    }
+}
+
+impl Sandbox {
+
+    pub fn summarize(&self, company: &Company) -> Summary {
+        Summary {
+            counts: self.count_changes(company),
+            changes: self.compare(company),
+        }
+    }
+
+    pub fn compare(&self, company: &Company) -> Vec<BalanceChange> {
+        // every account of the sandbox in tree order, then the accounts
+        // it no longer has
+        let mut changes = company
+            .get_account_tree()
+            .into_iter()
+            .map(|(account, _)| BalanceChange {
+                path: company.get_account_path(&account.id),
+                before: self.original
+                    .get_acccount_by_id(&account.id)
+                    .map(|_| self.original.get_rollup_balance_between(&account.id, None, None)),
+                after: company.get_rollup_balance_between(&account.id, None, None),
+            })
+            .collect::<Vec<BalanceChange>>();
+
+        for account in self.original.accounts.iter().filter(|account| company.get_acccount_by_id(&account.id).is_none()) {
+            changes.push(BalanceChange {
+                path: format!("{} (removed)", self.original.get_account_path(&account.id)),
+                before: Some(self.original.get_rollup_balance_between(&account.id, None, None)),
+                after: 0.0,
+            });
+        }
+
+        changes
+    }
+
+    pub fn count_changes(&self, company: &Company) -> BTreeMap<String, usize> {
+        // how many accounts and transactions were added, changed or deleted
+        let mut counts = BTreeMap::new();
+        for (kind, data) in events::diff(&self.original, company) {
+            // an account lists the transactions posted to it, so it only
+            // counts as changed when something else about it has
+            if kind == "account_changed" {
+                let before = self.original
+                    .get_acccount_by_id(data["id"].as_str().unwrap_or(""))
+                    .and_then(|account| serde_json::to_value(account).ok());
+                if before.map(without_transactions) == Some(without_transactions(data)) {
+                    continue;
+                }
+            }
+            *counts.entry(kind.replace('_', " ")).or_insert(0) += 1;
+        }
+
+        counts
+    }
+}
+
+pub fn import_transactions(company: &mut Company, path: &Path) -> Result<(usize, usize, usize), Box<dyn Error>> {
+    // posts the transactions of another ledger file whose accounts are
+    // found here by their full path. a transaction the ledger already has,
+    // such as from importing the same file twice or listed twice in the
+    // file, is left out. returns how many were posted, already there and skipped
+    let other = Company::default().load(path)?;
+    let mut existing = company.transactions
+        .iter()
+        .map(get_import_key)
+        .collect::<BTreeSet<_>>();
+    let mut posted = 0;
+    let mut duplicates = 0;
+    let mut skipped = 0;
+
+    for transaction in &other.transactions {
+        let debit = get_open_account_id(company, &other.get_account_path(&transaction.debit));
+        let credit = get_open_account_id(company, &other.get_account_path(&transaction.credit));
+
+        match (debit, credit) {
+            (Some(debit), Some(credit)) => {
+                let mut transaction = transaction.clone();
+                transaction.debit = debit.clone();
+                transaction.credit = credit.clone();
+                if !existing.insert(get_import_key(&transaction)) {
+                    duplicates += 1;
+                    continue;
+                }
+                // the bank has not seen it in this ledger
+                transaction
+                    .set_status(&debit, "uncleared")
+                    .set_status(&credit, "uncleared");
+                company.post_transaction(transaction);
+                posted += 1;
+            },
+            _ => skipped += 1,
+        }
+    }
+
+    Ok((posted, duplicates, skipped))
+}
+
+fn get_open_account_id(company: &Company, path: &str) -> Option<String> {
+    // no falling back to a name or an id, which mean something else in another ledger
+    company
+        .get_account_by_path(path)
+        .filter(|account| !account.closed)
+        .map(|account| account.id.clone())
+}
+
+fn get_import_key(transaction: &Transaction) -> (NaiveDate, String, String, String, String) {
+    // what makes two transactions the same for an import, the ids aside
+    let amount = transaction.amount
+        .parse::<f64>()
+        .map_or(transaction.amount.clone(), |amount| format!("{:.2}", amount));
+
+    (transaction.date, transaction.debit.clone(), transaction.credit.clone(), amount, transaction.memo.clone())
+}
+
+fn without_transactions(mut account: Value) -> Value {
+    if let Some(fields) = account.as_object_mut() {
+        fields.remove("transactions");
+    }
+
+    account
+}
@@ -0,0 +1,226 @@
+// "money serve" answers ledger requests over ZMQ so other tools can read
+// and change the ledger without parsing the database file.
+// requests are handled one at a time, so writes never race. the ledger is
+// read again whenever another program, such as the interface, has saved it
+
+use chrono::prelude::*;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::company::Company;
+use crate::events::Publisher;
+use crate::file_io::get_modified;
+use crate::protocol::{self, LedgerRequest, Response};
+use crate::query::Query;
+use crate::transaction::Transaction;
+
+
+pub struct Server {
+    pub company: Company,
+    pub db_path: String,
+    publisher: Option<Publisher>,
+    modified: Option<SystemTime>,
+    // when the database file was last read or written here
+}
+
+impl Server {
+
+    pub fn new(company: Company, db_path: &Path) -> Result<Self, Box<dyn Error>> {
+        let db_path = db_path.to_str().ok_or("database path is not valid unicode")?;
+
+        Ok(Server {
+            company,
+            db_path: db_path.to_string(),
+            publisher: None,
+            modified: get_modified(db_path),
+        })
+    }
+
+    pub fn run(&mut self, ctx: &zmq::Context, endpoint: &str, publish: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let socket = ctx.socket(zmq::REP)?;
+        socket.bind(endpoint)?;
+        println!("Serving {} on {}", self.db_path, endpoint);
+
+        if let Some(publish) = publish {
+            self.publisher = Some(Publisher::bind(publish)?);
+            println!("Publishing changes on {}", publish);
+        }
+
+        loop {
+            let mut msg = zmq::Message::new();
+            socket.recv(&mut msg, 0)?;
+
+            let response = match msg.as_str() {
+                Some(payload) => self.answer(payload),
+                None => Response::error("bad_request", "the request is not text"),
+            };
+            socket.send(&serde_json::to_string(&response)?[..], 0)?;
+        }
+    }
+
+    pub fn answer(&mut self, payload: &str) -> Response {
+        let request = match serde_json::from_str::<LedgerRequest>(payload) {
+            Ok(request) => request,
+            Err(err) => return Response::error("bad_request", &err.to_string()),
+        };
+
+        if request.version > protocol::VERSION {
+            let message = format!("version {} is not supported, the newest is {}", request.version, protocol::VERSION);
+            return Response::error("unsupported_version", &message);
+        }
+
+        if let Err(err) = self.reload() {
+            return Response::error("failed", &format!("cannot read the database: {}", err));
+        }
+
+        let params = &request.params;
+        let result = match &request.r#type[..] {
+            "balances" => self.get_balances(params),
+            "register" => self.get_register(params),
+            "chart" => Ok(self.get_chart()),
+            "add_transaction" => self.add_transaction(params),
+            "delete_transaction" => self.delete_transaction(params),
+            other => return Response::error("unknown_type", &format!("{} is not a known request", other)),
+        };
+
+        match result {
+            Ok(data) => Response::data(data),
+            Err(err) => Response::error("failed", &err.to_string()),
+        }
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        // a file saved since it was last read here replaces the ledger in
+        // memory, so a write never drops changes made by another program
+        let modified = get_modified(&self.db_path);
+        if modified.is_some() && modified != self.modified {
+            self.company = Company::default().load(&self.db_path)?;
+            self.modified = modified;
+        }
+
+        Ok(())
+    }
+
+    fn get_balances(&self, params: &Value) -> Result<Value, Box<dyn Error>> {
+        // rolled up balances, narrowed by an optional query
+        let query = Query::parse(&self.company, &get_optional(params, "query").unwrap_or_default())?;
+        let company = self.company.filtered(&query.filter);
+
+        let balances = company
+            .get_account_tree()
+            .into_iter()
+            .map(|(account, depth)| json!({
+                "id": account.id,
+                "account": company.get_account_path(&account.id),
+                "depth": depth,
+                "balance": company.get_rollup_balance_between(&account.id, None, None),
+            }))
+            .collect::<Vec<Value>>();
+
+        Ok(Value::from(balances))
+    }
+
+    fn get_register(&self, params: &Value) -> Result<Value, Box<dyn Error>> {
+        let query = Query::parse(&self.company, &get_optional(params, "query").unwrap_or_default())?;
+        let transactions = query.run(&self.company);
+
+        Ok(serde_json::to_value(transactions)?)
+    }
+
+    fn get_chart(&self) -> Value {
+        let chart = self.company
+            .get_account_tree()
+            .into_iter()
+            .map(|(account, depth)| json!({
+                "id": account.id,
+                "account": self.company.get_account_path(&account.id),
+                "name": account.name,
+                "type": account.r#type,
+                "parent": account.parent,
+                "depth": depth,
+                "closed": account.closed,
+            }))
+            .collect::<Vec<Value>>();
+
+        Value::from(chart)
+    }
+
+    fn add_transaction(&mut self, params: &Value) -> Result<Value, Box<dyn Error>> {
+        // accounts may be given by path, name or id
+        let debit = self.company.get_open_account_id(&get_required(params, "debit")?)?;
+        let credit = self.company.get_open_account_id(&get_required(params, "credit")?)?;
+
+        let amount = get_required(params, "amount")?;
+        if amount.parse::<f64>().map_or(true, |amount| amount <= 0.0) {
+            return Err(format!("{} is not a positive amount", amount).into());
+        }
+
+        let mut transaction = Transaction::new();
+        transaction
+            .set_debit(&debit)
+            .set_credit(&credit)
+            .set_amount(&amount)
+            .set_memo(&get_optional(params, "memo").unwrap_or_default())
+            .set_payee(&get_optional(params, "payee").unwrap_or_default());
+        if let Some(date) = get_optional(params, "date") {
+            transaction.date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+        }
+        if let Some(tags) = params.get("tags").and_then(|tags| tags.as_array()) {
+            for tag in tags.iter().filter_map(|tag| tag.as_str()) {
+                transaction.add_tag(tag);
+            }
+        }
+
+        let id = self.change("add transaction", |company| Ok(company.post_transaction(transaction)))?;
+
+        Ok(json!({ "id": id }))
+    }
+
+    fn delete_transaction(&mut self, params: &Value) -> Result<Value, Box<dyn Error>> {
+        let id = get_required(params, "id")?;
+        let confirmed = params.get("confirm").and_then(|confirm| confirm.as_bool()).unwrap_or(false);
+
+        self.change("delete transaction", |company| company.remove_transaction(&id, confirmed))?;
+
+        Ok(json!({ "id": id }))
+    }
+
+    fn change<F, T>(&mut self, label: &str, change: F) -> Result<T, Box<dyn Error>>
+    where F: FnOnce(&mut Company) -> Result<T, Box<dyn Error>>
+    {
+        // the change only stands once it is saved, and is only published then
+        let before = self.company.clone();
+
+        let result = change(&mut self.company).and_then(|result| {
+            self.company.write_to(&self.db_path)?;
+            self.modified = get_modified(&self.db_path);
+            Ok(result)
+        });
+
+        match &result {
+            Ok(_) => {
+                if let Some(publisher) = self.publisher.as_mut() {
+                    publisher.publish_changes(label, &before, &self.company);
+                }
+            },
+            Err(_) => self.company = before,
+        }
+
+        result
+    }
+}
+
+fn get_required(params: &Value, name: &str) -> Result<String, Box<dyn Error>> {
+    get_optional(params, name).ok_or_else(|| format!("{} is required", name).into())
+}
+
+fn get_optional(params: &Value, name: &str) -> Option<String> {
+    // numbers are accepted wherever text is, so ids and amounts can be either
+    match params.get(name) {
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(Value::Number(number)) => Some(number.to_string()),
+        _ => None,
+    }
+}
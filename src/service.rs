@@ -0,0 +1,265 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::protocol::{Request, Response};
+
+// how often a waiting worker wakes up to report progress and check for cancelling
+const POLL_INTERVAL_MS: i64 = 100;
+
+// the transports a service can be reached over
+pub const TRANSPORTS: [&str; 2] = ["zmq", "zmq-legacy"];
+
+// Where a service listens, how long to wait on it and what it is used for
+#[derive(Debug, Clone)]
+pub struct ServiceSettings {
+    pub name: String,
+    pub endpoint: String,
+    pub transport: String,
+    // "zmq" sends the JSON protocol, "zmq-legacy" the bare database
+    // to a service that predates it
+    pub timeout: Duration,
+    // how long each attempt waits for a reply
+    pub retries: usize,
+    pub actions: Vec<String>,
+    // the interface actions that use the service, such as "expense_report"
+}
+
+impl Default for ServiceSettings {
+    fn default() -> ServiceSettings {
+        ServiceSettings {
+            name: "report".to_string(),
+            endpoint: "tcp://127.0.0.1:6000".to_string(),
+            transport: "zmq".to_string(),
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            actions: vec!["expense_report".to_string()],
+        }
+    }
+}
+
+impl ServiceSettings {
+
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        // reads "name=<name>,endpoint=<address>" followed by any of
+        // transport=<zmq|zmq-legacy>, timeout=<ms>, retries=<n> and
+        // actions=<action>+<action>
+        let mut service = ServiceSettings {
+            name: String::new(),
+            endpoint: String::new(),
+            actions: Vec::new(),
+            ..ServiceSettings::default()
+        };
+
+        for part in spec.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("{} should be key=value", part))?;
+
+            match key {
+                "name" => service.name = value.to_string(),
+                "endpoint" => service.endpoint = value.to_string(),
+                "transport" => service.set_transport(value)?,
+                "timeout" => service.timeout = Duration::from_millis(value.parse()?),
+                "retries" => service.retries = value.parse()?,
+                "actions" => service.actions = value.split('+').map(|action| action.to_string()).collect(),
+                _ => return Err(format!("unknown service setting {}", key).into()),
+            }
+        }
+
+        if service.name.is_empty() || service.endpoint.is_empty() {
+            return Err(format!("{} needs a name and an endpoint", spec).into());
+        }
+
+        Ok(service)
+    }
+
+    pub fn set_transport(&mut self, transport: &str) -> Result<(), Box<dyn Error>> {
+        if !TRANSPORTS.contains(&transport) {
+            return Err(format!("{} is not a transport, use one of {}", transport, TRANSPORTS.join(", ")).into());
+        }
+        self.transport = transport.to_string();
+
+        Ok(())
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.transport == "zmq-legacy"
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{:<12} {:<26} {:<11} {:>6}ms {:>2} retries  {}",
+            self.name,
+            self.endpoint,
+            self.transport,
+            self.timeout.as_millis(),
+            self.retries,
+            self.actions.join(", ")
+        )
+    }
+}
+
+// The ServiceRegistry names every external service Money talks to
+#[derive(Debug, Clone)]
+pub struct ServiceRegistry {
+    pub services: Vec<ServiceSettings>,
+}
+
+impl Default for ServiceRegistry {
+    fn default() -> ServiceRegistry {
+        ServiceRegistry {
+            services: vec![ServiceSettings::default()],
+        }
+    }
+}
+
+impl ServiceRegistry {
+
+    pub fn insert(&mut self, service: ServiceSettings) -> &mut Self {
+        // a service with the same name is replaced
+        self.services.retain(|existing| existing.name != service.name);
+        self.services.push(service);
+
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ServiceSettings> {
+        self.services.iter().find(|service| service.name == name)
+    }
+
+    pub fn get_or_insert(&mut self, name: &str) -> &mut ServiceSettings {
+        if self.get(name).is_none() {
+            self.services.push(ServiceSettings {
+                name: name.to_string(),
+                actions: Vec::new(),
+                ..ServiceSettings::default()
+            });
+        }
+
+        self.services
+            .iter_mut()
+            .find(|service| service.name == name)
+            .expect("the service was just inserted")
+    }
+
+    pub fn get_for_action(&self, action: &str) -> Option<&ServiceSettings> {
+        // the last service registered for an action wins
+        self.services
+            .iter()
+            .rev()
+            .find(|service| service.actions.iter().any(|candidate| candidate == action))
+    }
+}
+
+// What the worker tells the interface while a request runs
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    Retrying(usize, String),
+    // the attempt about to start and why the last one failed
+    Finished(Result<PathBuf, String>),
+}
+
+// A ServiceJob is one request to the report service running on its own
+// thread, so a slow or missing service cannot freeze the interface
+pub struct ServiceJob {
+    receiver: Receiver<ServiceEvent>,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl ServiceJob {
+
+    pub fn start(settings: &ServiceSettings, request: Request, directory: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let settings = settings.clone();
+        let directory = directory.to_path_buf();
+        let flag = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            let result = request
+                .to_payload(settings.is_legacy())
+                .map_err(|err| err.to_string())
+                .and_then(|payload| send_request(&settings, &payload, &flag, &sender))
+                .and_then(|response| {
+                    response
+                        .into_artifact()
+                        .and_then(|artifact| artifact.save(&directory))
+                        .map_err(|err| err.to_string())
+                });
+            // the interface may have gone away, which is fine
+            let _ = sender.send(ServiceEvent::Finished(result));
+        });
+
+        ServiceJob { receiver, cancelled, started: Instant::now() }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        // how long the request has been running, across every attempt
+        self.started.elapsed()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn poll(&self) -> Vec<ServiceEvent> {
+        // everything the worker has sent since the last poll, without waiting
+        self.receiver.try_iter().collect()
+    }
+}
+
+fn send_request(settings: &ServiceSettings, payload: &str, cancelled: &AtomicBool, sender: &Sender<ServiceEvent>) -> Result<Response, String> {
+    let ctx = zmq::Context::new();
+    let attempts = settings.retries + 1;
+    let timeout = settings.timeout.as_secs_f64().max(0.001);
+    let mut failure = String::new();
+
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            let _ = sender.send(ServiceEvent::Retrying(attempt + 1, failure.clone()));
+        }
+
+        // a REQ socket that never got its reply cannot send again,
+        // so every attempt gets a fresh socket that drops any queued message
+        let socket = ctx.socket(zmq::REQ).map_err(|err| err.to_string())?;
+        socket.set_linger(0).map_err(|err| err.to_string())?;
+
+        if let Err(err) = socket.connect(&settings.endpoint).and_then(|_| socket.send(payload, 0)) {
+            failure = err.to_string();
+            continue;
+        }
+
+        let started = Instant::now();
+        loop {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err("cancelled".to_string());
+            }
+
+            let elapsed = started.elapsed().as_secs_f64();
+            if elapsed >= timeout {
+                failure = format!("no reply from {} within {:.1}s", settings.endpoint, timeout);
+                break;
+            }
+
+            match socket.poll(zmq::POLLIN, POLL_INTERVAL_MS) {
+                Ok(0) => continue,
+                Ok(_) => {
+                    let reply = socket.recv_msg(0).map_err(|err| err.to_string())?;
+                    let reply = reply.as_str().ok_or("the service reply is not text")?;
+                    return Response::parse(reply).map_err(|err| err.to_string());
+                },
+                Err(err) => {
+                    failure = err.to_string();
+                    break;
+                },
+            }
+        }
+    }
+
+    Err(format!("gave up after {} attempts: {}", attempts, failure))
+}
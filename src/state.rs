@@ -0,0 +1,60 @@
+// the state file remembers what Money needs to know about the user between
+// runs, such as the newest version whose features they have been shown
+// and the commands they ran.
+// unlike the settings file it is written by Money and not meant to be edited
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::file_io;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(default)]
+    pub last_seen_version: Option<String>,
+    // None until the first run is over
+    #[serde(default)]
+    pub command_history: Vec<String>,
+    // the commands run from the input, oldest first
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    // where the state was read from and is saved to
+}
+
+impl State {
+
+    pub fn load(path: &Path) -> Result<State, Box<dyn Error>> {
+        // no file yet means this is the first run
+        let mut state = if path.exists() {
+            let text = file_io::read(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+            serde_json::from_str(&text).map_err(|err| format!("{} is not valid: {}", path.display(), err))?
+        }
+        else {
+            State::default()
+        };
+        state.path = Some(path.to_path_buf());
+
+        Ok(state)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let location = path.to_str().ok_or("state path is not valid unicode")?;
+        file_io::truncate(location)?;
+        file_io::write(location, &serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+pub fn get_default_path() -> Option<PathBuf> {
+    // state.json under $XDG_DATA_HOME/money, or the platform equivalent
+    dirs::data_local_dir().map(|dir| dir.join("money").join("state.json"))
+}
@@ -0,0 +1,262 @@
+// themes hold the colors of the interface by what they mean rather than
+// where they are drawn, so a palette can be swapped in one place.
+// see "Themes" in the README
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use tui::style::{Color, Modifier, Style};
+
+// the themes built into Money
+pub const PRESETS: [&str; 5] = ["default", "dark", "light", "high-contrast", "monochrome"];
+
+// the parts of a theme the settings file can change
+pub const SLOTS: [&str; 13] = [
+    "input", "gauge", "gauge_label", "positive", "negative", "debit",
+    "credit", "warning", "chart", "chart_alt", "bar_value", "heading",
+    "selected",
+];
+
+// One [themes.<name>] table in the settings file. it starts from the preset
+// named by base, or the preset of the same name, and changes the slots given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeEntry {
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+    // slot name to color, such as negative = "light-red"
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub input: Style,
+    // the input line while editing
+    pub gauge: Style,
+    pub gauge_label: Style,
+    pub positive: Style,
+    pub negative: Style,
+    // amounts above and below zero
+    pub debit: Style,
+    pub credit: Style,
+    // the debit and credit side of a transaction or balance
+    pub warning: Style,
+    // such as an overspent budget
+    pub chart: Style,
+    pub chart_alt: Style,
+    pub bar_value: Style,
+    // the figures drawn on top of a bar
+    pub heading: Style,
+    pub selected: Style,
+    // the picked row of a list
+    pub effects: bool,
+    // blinking and italics, which some readers find hard to follow
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            name: "default".to_string(),
+            input: fg(Color::Yellow),
+            gauge: fg(Color::Yellow),
+            gauge_label: fg(Color::Red).add_modifier(Modifier::ITALIC | Modifier::BOLD),
+            positive: fg(Color::Green),
+            negative: fg(Color::Red),
+            debit: fg(Color::LightBlue),
+            credit: fg(Color::LightMagenta),
+            warning: fg(Color::Red).add_modifier(Modifier::BOLD),
+            chart: fg(Color::Cyan),
+            chart_alt: fg(Color::Yellow),
+            bar_value: fg(Color::Black),
+            heading: Style::default().add_modifier(Modifier::BOLD),
+            selected: Style::default().add_modifier(Modifier::REVERSED),
+            effects: true,
+        }
+    }
+}
+
+impl Theme {
+
+    pub fn preset(name: &str) -> Option<Theme> {
+        let theme = Theme::default();
+
+        let theme = match name {
+            "default" => theme,
+            "dark" => Theme {
+                input: fg(Color::LightYellow),
+                gauge: fg(Color::LightCyan),
+                gauge_label: fg(Color::White).add_modifier(Modifier::ITALIC | Modifier::BOLD),
+                positive: fg(Color::LightGreen),
+                negative: fg(Color::LightRed),
+                warning: fg(Color::LightRed).add_modifier(Modifier::BOLD),
+                chart: fg(Color::LightCyan),
+                chart_alt: fg(Color::LightYellow),
+                ..theme
+            },
+            "light" => Theme {
+                input: fg(Color::Blue),
+                gauge: fg(Color::Blue),
+                gauge_label: fg(Color::Black).add_modifier(Modifier::ITALIC | Modifier::BOLD),
+                debit: fg(Color::Blue),
+                credit: fg(Color::Magenta),
+                chart: fg(Color::Blue),
+                chart_alt: fg(Color::Magenta),
+                bar_value: fg(Color::White),
+                ..theme
+            },
+            // blue against orange rather than green against red, so the
+            // colors stay apart for the common kinds of colorblindness,
+            // and negative amounts are underlined as well
+            "high-contrast" => Theme {
+                input: fg(Color::White).add_modifier(Modifier::BOLD),
+                gauge: fg(Color::White),
+                gauge_label: fg(Color::White).add_modifier(Modifier::BOLD),
+                positive: fg(Color::Rgb(86, 180, 233)).add_modifier(Modifier::BOLD),
+                negative: fg(Color::Rgb(230, 159, 0)).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                debit: fg(Color::Rgb(86, 180, 233)),
+                credit: fg(Color::Rgb(230, 159, 0)),
+                warning: fg(Color::Rgb(230, 159, 0)).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                chart: fg(Color::Rgb(86, 180, 233)),
+                chart_alt: fg(Color::Rgb(240, 228, 66)),
+                bar_value: fg(Color::Black).add_modifier(Modifier::BOLD),
+                ..theme
+            },
+            // no color at all, only weight and underlines
+            "monochrome" => Theme {
+                input: Style::default().add_modifier(Modifier::BOLD),
+                gauge: Style::default(),
+                gauge_label: Style::default().add_modifier(Modifier::BOLD),
+                positive: Style::default(),
+                negative: Style::default().add_modifier(Modifier::UNDERLINED),
+                debit: Style::default(),
+                credit: Style::default(),
+                warning: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                chart: Style::default(),
+                chart_alt: Style::default(),
+                bar_value: Style::default().add_modifier(Modifier::REVERSED),
+                ..theme
+            },
+            _ => return None,
+        };
+
+        Some(Theme { name: name.to_string(), ..theme })
+    }
+
+    pub fn from_settings(name: &str, themes: &BTreeMap<String, ThemeEntry>) -> Result<Theme, Box<dyn Error>> {
+        let entry = match themes.get(name) {
+            Some(entry) => entry,
+            None => {
+                return Theme::preset(name).ok_or_else(|| {
+                    format!("{} is not a theme, use one of {} or define it under [themes]", name, PRESETS.join(", ")).into()
+                });
+            },
+        };
+
+        let base = entry.base.as_deref().unwrap_or(if PRESETS.contains(&name) { name } else { "default" });
+        let mut theme = Theme::preset(base).ok_or_else(|| format!("{} is not a preset to base {} on", base, name))?;
+        theme.name = name.to_string();
+
+        for (slot, color) in &entry.colors {
+            theme.set_color(slot, parse_color(color)?)?;
+        }
+
+        Ok(theme)
+    }
+
+    pub fn set_color(&mut self, slot: &str, color: Color) -> Result<&mut Self, Box<dyn Error>> {
+        // only the color changes, any weight or underline of the preset stays
+        let style = match slot {
+            "input" => &mut self.input,
+            "gauge" => &mut self.gauge,
+            "gauge_label" => &mut self.gauge_label,
+            "positive" => &mut self.positive,
+            "negative" => &mut self.negative,
+            "debit" => &mut self.debit,
+            "credit" => &mut self.credit,
+            "warning" => &mut self.warning,
+            "chart" => &mut self.chart,
+            "chart_alt" => &mut self.chart_alt,
+            "bar_value" => &mut self.bar_value,
+            "heading" => &mut self.heading,
+            "selected" => &mut self.selected,
+            _ => return Err(format!("{} is not part of a theme, use one of {}", slot, SLOTS.join(", ")).into()),
+        };
+        *style = style.fg(color);
+
+        Ok(self)
+    }
+
+    pub fn effects(mut self, effects: bool) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    pub fn style(&self, style: Style) -> Style {
+        // removes blinking and italics when effects are turned off
+        if self.effects {
+            return style;
+        }
+
+        style.remove_modifier(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK | Modifier::ITALIC)
+    }
+
+    pub fn amount(&self, amount: f64) -> Style {
+        if amount < 0.0 {
+            self.negative
+        }
+        else if amount > 0.0 {
+            self.positive
+        }
+        else {
+            Style::default()
+        }
+    }
+
+    pub fn bar(&self, style: Style) -> Style {
+        // the figures on a bar are drawn over the bar color
+        match style.fg {
+            Some(color) => self.bar_value.bg(color),
+            None => self.bar_value,
+        }
+    }
+}
+
+pub fn parse_color(text: &str) -> Result<Color, Box<dyn Error>> {
+    // a name such as "light-red", "#rrggbb" or a terminal color number
+    let name = text.trim().to_lowercase().replace(['-', '_', ' '], "");
+
+    if let Some(hex) = name.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6);
+        let value = value.ok_or_else(|| format!("{} is not a #rrggbb color", text))?;
+        return Ok(Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8));
+    }
+    if let Ok(index) = name.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match &name[..] {
+        "reset" | "none" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("{} is not a color", text).into()),
+    };
+
+    Ok(color)
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
@@ -1,6 +1,9 @@
 use chrono::prelude::*;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::error::Error;
 use crate::company::Company;
+use crate::account::Account;
 
 
 // Transactions move money between accounts
@@ -11,9 +14,26 @@ pub struct Transaction {
     pub debit: String,
     pub amount: String,
     pub memo: String,
-    pub date: NaiveDate
+    pub date: NaiveDate,
     // see https://docs.rs/chrono/0.4.19/chrono/
     // for date related things
+    #[serde(default = "default_status")]
+    pub debit_status: String,
+    #[serde(default = "default_status")]
+    pub credit_status: String,
+    // each side of the transaction is one of "uncleared", "pending",
+    // "cleared" or "reconciled" as confirmed by the bank
+    #[serde(default)]
+    pub payee: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // tags are stored without the leading #
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>
+}
+
+fn default_status() -> String {
+    "uncleared".to_string()
 }
 
 impl Transaction {
@@ -25,7 +45,12 @@ impl Transaction {
             debit: "".to_string(),
             amount: "".to_string(),
             memo: "".to_string(),
-            date: Utc::today().naive_utc()
+            date: Utc::today().naive_utc(),
+            debit_status: default_status(),
+            credit_status: default_status(),
+            payee: "".to_string(),
+            tags: Vec::new(),
+            metadata: BTreeMap::new()
         }
     }
 
@@ -73,5 +98,103 @@ impl Transaction {
         self
     }
 
+    pub fn set_payee(&mut self, payee: &str) -> &mut Self {
+        // sets who the money was paid to or received from
+        self.payee = payee.trim().to_string();
+
+        self
+    }
+
+    pub fn add_tag(&mut self, tag: &str) -> &mut Self {
+        // adds the tag if not already included
+        let tag = tag.trim().trim_start_matches('#');
+
+        if !tag.is_empty() && !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+
+        self
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> Result<&mut Self, Box<dyn Error>> {
+        // tags match whatever their case, like has_tag
+        let tag = tag.trim().trim_start_matches('#');
+        let count = self.tags.len();
+        self.tags.retain(|element| !element.eq_ignore_ascii_case(tag));
+
+        if self.tags.len() == count {
+            return Err(format!("transaction_id {} is not tagged {}", self.id, tag).into());
+        }
+
+        Ok(self)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags.iter().any(|element| element.eq_ignore_ascii_case(tag))
+    }
+
+    pub fn set_metadata(&mut self, key: &str, value: &str) -> &mut Self {
+        // sets a key/value pair. an empty value removes the key
+        if value.is_empty() {
+            self.metadata.remove(key);
+        }
+        else {
+            self.metadata.insert(key.to_string(), value.to_string());
+        }
+
+        self
+    }
+
+    pub fn get_status(&self, account_id: &str) -> &str {
+        // the status of the posting against the given account
+        if self.debit == account_id {
+            &self.debit_status
+        }
+        else if self.credit == account_id {
+            &self.credit_status
+        }
+        else {
+            ""
+        }
+    }
+
+    pub fn set_status(&mut self, account_id: &str, status: &str) -> &mut Self {
+        // sets the status of the posting against the given account
+        let good_status = matches!(status, "uncleared" | "pending" | "cleared" | "reconciled");
+
+        if !good_status {
+            return self;
+        }
+
+        if self.debit == account_id {
+            self.debit_status = status.to_string();
+        }
+        if self.credit == account_id {
+            self.credit_status = status.to_string();
+        }
 
-}
\ No newline at end of file
+        self
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        self.debit_status == "reconciled" || self.credit_status == "reconciled"
+    }
+
+    pub fn get_signed_amount(&self, account: &Account) -> f64 {
+        // the amount as it affects the balance of the account.
+        // debits increase debit accounts and credits increase credit accounts
+        let amount = self.amount.parse::<f64>().unwrap_or(0.0);
+        let is_debit = account.r#type == "d";
+
+        if self.debit == account.id {
+            if is_debit { amount } else { -amount }
+        }
+        else if self.credit == account.id {
+            if is_debit { -amount } else { amount }
+        }
+        else {
+            0.0
+        }
+    }
+}
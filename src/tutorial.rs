@@ -0,0 +1,168 @@
+// "money tutorial" walks through the basics on a scratch copy of a sample
+// ledger. each step is checked against what the app shows before the next
+// one starts, and the user's own database is never opened
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use crate::app::{App, Focus};
+use crate::company::Company;
+use crate::file_io;
+use crate::keymap::{Action, Keymap};
+use crate::manual;
+
+// the same ledger as example.json, built in so the tutorial runs anywhere
+const SAMPLE_LEDGER: &str = include_str!("../example.json");
+
+pub struct Step {
+    pub title: &'static str,
+    pub instructions: &'static [&'static str],
+    // keys are written as {action} like the manual
+}
+
+pub const STEPS: [Step; 4] = [
+    Step {
+        title: "Add a transaction",
+        instructions: &[
+            "Press {new_transaction} to open the new transaction view, then {edit} to type.",
+            "Enter: 2014-11-29 Expenses:Food Assets 12.50 \"lunch out\"",
+            "That moves 12.50 from Assets to Expenses:Food. Press Enter to post it.",
+        ],
+    },
+    Step {
+        title: "View a register",
+        instructions: &[
+            "Press {register}, then {edit}, and enter: Food",
+            "Or run the command :reg Food to list it in the messages.",
+            "The register lists every transaction of Expenses:Food with a running total, including the one you added.",
+        ],
+    },
+    Step {
+        title: "Run the balance sheet",
+        instructions: &[
+            "Press {balance_sheet} to list the balance of every account.",
+            "The query from the register still narrows the balances to Expenses:Food. Enter nothing in the register to see every balance again.",
+        ],
+    },
+    Step {
+        title: "Undo an edit",
+        instructions: &[
+            "Press {undo} to take back the transaction you added.",
+            "Open the balance sheet again to see it gone, and {redo} would put it back.",
+        ],
+    },
+];
+
+pub struct Tutorial {
+    pub step: usize,
+    // the step being worked on, STEPS.len() once finished
+    pub scratch: PathBuf,
+    // the directory holding the copy of the sample ledger
+    sample_max_id: usize,
+    // transactions with a higher id were added during the tutorial
+    added_at_step: usize,
+    // how many were added when the current step started
+    register_listed: bool,
+    // :reg listed a transaction added during the tutorial
+}
+
+impl Tutorial {
+
+    pub fn create() -> Result<Tutorial, Box<dyn Error>> {
+        // a directory of its own under the temporary directory, so two
+        // tutorials at once do not share a ledger
+        let scratch = env::temp_dir().join(format!("money-tutorial-{}", std::process::id()));
+        fs::create_dir_all(&scratch)?;
+
+        // from here on the directory is removed if anything goes wrong
+        let mut tutorial = Tutorial {
+            step: 0,
+            scratch,
+            sample_max_id: 0,
+            added_at_step: 0,
+            register_listed: false,
+        };
+        let location = tutorial.get_database();
+        let location = location.to_str().ok_or("the temporary directory is not valid unicode")?;
+        file_io::truncate(location)?;
+        file_io::write(location, SAMPLE_LEDGER)?;
+
+        let mut sample = Company::default().load(tutorial.get_database())?;
+        tutorial.sample_max_id = sample.max_id_transaction().unwrap_or(0);
+
+        Ok(tutorial)
+    }
+
+    pub fn get_database(&self) -> PathBuf {
+        self.scratch.join("db.json")
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= STEPS.len()
+    }
+
+    pub fn get_instructions(&self, keymap: &Keymap) -> Vec<String> {
+        match STEPS.get(self.step) {
+            Some(step) => step.instructions.iter().map(|line| manual::fill_keys(line, keymap)).collect(),
+            None => vec![format!(
+                "That is the tutorial. Press {} to leave, the scratch ledger is thrown away. Run money help for the manual.",
+                keymap.get_keys(Action::Quit)
+            )],
+        }
+    }
+
+    pub fn check(&mut self, app: &App) -> Option<String> {
+        // moves to the next step once the app shows the current one is done,
+        // returning what to tell the user
+        let added = self.count_added(&app.company);
+
+        let done = match self.step {
+            0 => added > self.added_at_step,
+            1 => {
+                self.register_listed
+                    || (app.focus == Focus::Register
+                        && app.query.run(&app.company).iter().any(|transaction| self.is_added(&transaction.id)))
+            },
+            2 => app.focus == Focus::BalanceSheet,
+            3 => added < self.added_at_step && !app.history.redo.is_empty(),
+            _ => false,
+        };
+        if !done {
+            return None;
+        }
+
+        let title = STEPS[self.step].title;
+        self.step += 1;
+        self.added_at_step = added;
+
+        if self.is_finished() {
+            Some(format!("Tutorial: {} done. You have finished the tutorial", title))
+        }
+        else {
+            Some(format!("Tutorial: {} done. Next, {}", title, STEPS[self.step].title.to_lowercase()))
+        }
+    }
+
+    pub fn list_register<'a>(&mut self, ids: impl IntoIterator<Item = &'a str>) {
+        // the register printed by :reg counts as much as the register view
+        if self.step == 1 && ids.into_iter().any(|id| self.is_added(id)) {
+            self.register_listed = true;
+        }
+    }
+
+    fn count_added(&self, company: &Company) -> usize {
+        company.transactions.iter().filter(|transaction| self.is_added(&transaction.id)).count()
+    }
+
+    fn is_added(&self, id: &str) -> bool {
+        id.parse::<usize>().is_ok_and(|id| id > self.sample_max_id)
+    }
+}
+
+impl Drop for Tutorial {
+    fn drop(&mut self) {
+        // the scratch ledger goes with the tutorial, however the app ends
+        let _ = fs::remove_dir_all(&self.scratch);
+    }
+}
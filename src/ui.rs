@@ -8,19 +8,36 @@ use std::{
 use tui::{
     Frame,
     backend::Backend,
+    buffer::Buffer,
     style::{Color, Modifier, Style},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     text::{Span, Spans, Text},
-    widgets::{Block, BorderType, Borders, ListItem, Paragraph, List, Gauge, Wrap},
+    widgets::{
+        Axis, BarChart, Block, BorderType, Borders, Chart, Dataset, GraphType,
+        ListItem, ListState, Paragraph, List, Widget, Wrap,
+    },
+    symbols,
 };
 
 use unicode_width::UnicodeWidthStr;
 
+use chrono::prelude::*;
+
 use crate::company::Company;
+use crate::recurrence::Recurrence;
+use crate::cashflow::CashFlow;
+use crate::report::ExpenseReport;
 use crate::account::Account;
+use crate::keymap::Action;
+use crate::features;
+use crate::manual;
+use crate::tutorial;
+use crate::mouse::{self, Row, Screen, Target};
 use crate::transaction::Transaction;
 use crate::app::{App, InputMode, Focus};
 
+// the frames of the spinner shown while the report service is working
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 pub fn capture_input<'a>() -> io::Result<String> {
     // see https://doc.rust-lang.org/std/io/struct.Stdin.html
@@ -67,6 +84,10 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         )
         .split(f.size());
 
+    // what the mouse can click is found again each frame
+    app.screen = Screen::default();
+    app.screen.input = chunks[1];
+
     draw_text(f, app, chunks[0]);
     draw_input(f, app, chunks[1]);
     draw_messages(f, app, chunks[2]);
@@ -89,30 +110,50 @@ where
         .split(area);
 
     let (msg, style) = match app.input_mode {
+        // a chord shows the keys pressed so far until it is finished
+        InputMode::Normal if app.keymap.is_pending() => (
+            vec![
+                Span::styled(app.keymap.get_pending(), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" is waiting for the rest of a chord. Any other key ends it and acts on its own."),
+            ],
+            Style::default(),
+        ),
         InputMode::Normal => (
             vec![
                 Span::raw("Press "),
-                Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(app.keymap.get_keys(Action::Quit), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" to exit, "),
-                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(app.keymap.get_keys(Action::Edit), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" to start editing."),
             ],
-            Style::default().add_modifier(Modifier::SLOW_BLINK),
+            app.theme.style(Style::default().add_modifier(Modifier::SLOW_BLINK)),
         ),
-        InputMode::Editing => (
-            vec![
+        InputMode::Editing => {
+            let mut msg = vec![
                 Span::raw("Press "),
                 Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" to stop editing, "),
                 Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to record the message"),
-            ],
-            Style::default(),
-        )
+                Span::raw(" to enter it, "),
+                Span::styled("Tab", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to complete"),
+            ];
+            // earlier commands come back on the command line only
+            if app.input.starts_with(':') {
+                msg.push(Span::raw(", "));
+                msg.push(Span::styled("Up", Style::default().add_modifier(Modifier::BOLD)));
+                msg.push(Span::raw(" for earlier commands"));
+            }
+            (msg, Style::default())
+        }
     };
 
     let mut text = Text::from(Spans::from(msg));
     text.patch_style(style);
+    // a reminder that changes are not being saved
+    if app.sandbox.is_some() {
+        text.lines[0].0.push(Span::styled("  SANDBOX", app.theme.warning));
+    }
     let help_message = Paragraph::new(text);
 
     f.render_widget(help_message, chunks[0]);
@@ -132,19 +173,22 @@ where
         )
         .split(area);
 
-    let label = Span::styled(
-        format!("{:.2}%", app.progress * 100.0),
-        Style::default()
-            .fg(Color::Red)
-            .add_modifier(Modifier::ITALIC | Modifier::BOLD),
-    );
-    let gauge = Gauge::default()
-        .block(Block::default().title("Gauge1").borders(Borders::ALL))
-        .gauge_style(Style::default().fg(Color::Yellow))
-        .ratio(app.progress)
-        .label(label)
-        .use_unicode(true);
-    f.render_widget(gauge, chunks[0]);
+    // a request cannot tell how far along it is, so a spinner
+    // shows it is still waiting and for how long
+    let status = match &app.service_job {
+        Some(job) => {
+            let elapsed = job.elapsed();
+            let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+            vec![
+                Span::styled(format!("{} ", frame), app.theme.style(app.theme.gauge)),
+                Span::styled(format!("waiting {:.1}s", elapsed.as_secs_f64()), app.theme.style(app.theme.gauge_label)),
+            ]
+        },
+        None => vec![Span::styled("idle", app.theme.style(app.theme.gauge_label))],
+    };
+    let paragraph = Paragraph::new(Spans::from(status))
+        .block(Block::default().title("Report Service").borders(Borders::ALL));
+    f.render_widget(paragraph, chunks[0]);
 
 }
 
@@ -167,7 +211,7 @@ where
     let input = Paragraph::new(app.input.as_ref())
     .style(match app.input_mode {
         InputMode::Normal => Style::default(),
-        InputMode::Editing => Style::default().fg(Color::Yellow),
+        InputMode::Editing => app.theme.input,
     })
     .block(Block::default().borders(Borders::ALL).title("Input"));
 
@@ -206,16 +250,18 @@ where
         .split(area);
 
     // print out the help text
+    app.screen.menu = chunks[0];
     let text = get_menu_text(app);
     let menu = Paragraph::new(text);
     f.render_widget(menu, chunks[0]);
 
 
-    // print out the message window
+    // print out the message window, from where the wheel left it
     let messages: Vec<ListItem> = app
         .messages
         .iter()
         .enumerate()
+        .skip(app.messages_scroll)
         .map(|(i, m)| {
             let content = vec![Spans::from(Span::raw(format!("{}: {}", i, m)))];
             ListItem::new(content)
@@ -224,37 +270,425 @@ where
 
     let messages = 
         List::new(messages).block(Block::default().borders(Borders::ALL).title("Messages").border_type(BorderType::Thick));
-    f.render_widget(messages, chunks[2]);
 
+    // the tutorial keeps its steps above the messages
+    if let Some(tutorial) = &app.tutorial {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(chunks[2]);
+        f.render_widget(messages, parts[1]);
+        app.screen.messages = parts[1];
+
+        let mut text = Text::from("");
+        for (i, step) in tutorial::STEPS.iter().enumerate() {
+            let (marker, style) = if i < tutorial.step {
+                ("[x]", app.theme.positive)
+            }
+            else if i == tutorial.step {
+                ("[>]", app.theme.heading)
+            }
+            else {
+                ("[ ]", Style::default())
+            };
+            text.extend(Text::styled(format!("{} {}", marker, step.title), style));
+        }
+        text.extend(Text::raw(""));
+        for line in tutorial.get_instructions(&app.keymap) {
+            text.extend(Text::raw(line));
+        }
+
+        let steps = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title("Tutorial"))
+            .wrap(Wrap { trim: false });
+        f.render_widget(steps, parts[0]);
+    }
+    else {
+        f.render_widget(messages, chunks[2]);
+        app.screen.messages = chunks[2];
+    }
+
+
+    // buttons for what is going on sit under the viewer
+    let buttons = mouse::get_buttons(app);
+    let viewer_area = if buttons.is_empty() {
+        chunks[1]
+    }
+    else {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+            .split(chunks[1]);
+        draw_buttons(f, app, buttons, parts[1]);
+        parts[0]
+    };
+    app.screen.viewer = viewer_area;
 
     // print out the the main viewer
+    if app.focus == Focus::Charts {
+        draw_chart_tree(f, app, viewer_area);
+        return;
+    }
+    if app.focus == Focus::Dashboard {
+        draw_dashboard(f, app, viewer_area);
+        return;
+    }
+    if app.focus == Focus::WhatsNew {
+        draw_whats_new(f, app, viewer_area);
+        return;
+    }
+
+    let scroll = app.get_viewer_scroll();
     let text = fill_viewer(app);
+    let starts = get_line_starts(&text, viewer_area.width.saturating_sub(2) as usize);
     let viewer = Paragraph::new(text)
         .block(Block::default().title("Viewer").borders(Borders::ALL))
-        .wrap(Wrap { trim: false});
-    f.render_widget(viewer, chunks[1]);
+        .wrap(Wrap { trim: false})
+        .scroll((scroll, 0));
+    f.render_widget(viewer, viewer_area);
+    app.screen.viewer_lines = starts.last().copied().unwrap_or(0) as u16;
+    app.screen.place_lines(&starts, scroll);
 
 }
 
-fn get_menu_text(app: &mut App) -> Text {
-    let mut text = Text::from("(b) List the current balance for your portfolio\n");
-    text.extend(Text::raw("(t) Enter a new transaction\n"));
-    text.extend(Text::raw("(r) Examine the register for an account\n"));
-    text.extend(Text::raw("(d) Delete a transaction\n"));
-    text.extend(Text::raw("(l) List the chart of accounts\n"));
-    text.extend(Text::raw("(g) Generate an expense report\n"));
-    text.extend(Text::raw("(q) Quit the program\n"));
-    
+fn draw_buttons<B>(f: &mut Frame<B>, app: &mut App, buttons: Vec<(String, Target)>, area: Rect)
+where
+    B: Backend,
+{
+    // each button is its label in brackets, one space apart
+    let mut spans = Vec::new();
+    let mut x = area.x;
+    for (label, target) in buttons {
+        let label = format!("[ {} ]", label);
+        let width = (label.width() as u16).min((area.x + area.width).saturating_sub(x));
+        app.screen.buttons.push((Rect::new(x, area.y, width, 1), target));
+        x += width + 1;
+
+        spans.push(Span::styled(label, Style::default().add_modifier(Modifier::REVERSED)));
+        spans.push(Span::raw(" "));
+    }
+
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+fn draw_chart_tree<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    // keep the selection on the tree after accounts are removed or collapsed
+    let count = app.get_chart_nodes().len();
+    if app.chart_selected >= count {
+        app.chart_selected = count.saturating_sub(1);
+    }
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    app.screen.add_list(inner, &vec![1; count], app.chart_selected, Row::Account);
+    let nodes = app.get_chart_nodes();
+
+    // leave room for the borders and the balance column
+    let width = area.width.saturating_sub(16) as usize;
+
+    let items: Vec<ListItem> = nodes
+        .iter()
+        .map(|(account, depth)| {
+            let marker = if app.company.get_children(&account.id).is_empty() {
+                "  "
+            }
+            else if app.chart_collapsed.contains(&account.id) {
+                "+ "
+            }
+            else {
+                "- "
+            };
+            let name = format!("{}{}{}", "  ".repeat(*depth), marker, account.name);
+            let balance = app.company.get_rollup_balance_between(&account.id, None, None);
+
+            ListItem::new(Spans::from(Span::raw(format!("{:<width$}{:>12}", name, app.format_amount(balance), width = width))))
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    if !items.is_empty() {
+        state.select(Some(app.chart_selected));
+    }
+
+    let title = match app.chart_action.as_deref() {
+        Some("add") => "Chart of Accounts - enter the name of the new subaccount".to_string(),
+        Some("rename") => "Chart of Accounts - enter the new name".to_string(),
+        _ => format!(
+            "Chart of Accounts - {} register, {} add, {} rename, {} and {} fold",
+            app.keymap.get_keys(Action::Select),
+            app.keymap.get_keys(Action::AddAccount),
+            app.keymap.get_keys(Action::RenameAccount),
+            app.keymap.get_keys(Action::Left),
+            app.keymap.get_keys(Action::Right)
+        ),
+    };
+
+    let tree = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selected);
+    f.render_stateful_widget(tree, area, &mut state);
+}
+
+fn draw_whats_new<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let title = match &app.state.last_seen_version {
+        Some(version) if version != features::CURRENT_VERSION => format!("What's new since {}", version),
+        Some(_) => format!("Features of Money {}", features::CURRENT_VERSION),
+        None => format!("Welcome to Money {}, here is what it can do", features::CURRENT_VERSION),
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(0)
+        .constraints([Constraint::Min(1), Constraint::Length(2)].as_ref())
+        .split(inner);
+
+    // the picked feature also shows its benefit and cost
+    let width = chunks[0].width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = app.notices
+        .iter()
+        .enumerate()
+        .map(|(idx, feature)| {
+            let name = match feature.action {
+                Some(action) => format!("{} ({})", feature.name, app.keymap.get_keys(action)),
+                None => feature.name.to_string(),
+            };
+            let mut lines = vec![Spans::from(Span::raw(name))];
+            if idx == app.notice_selected {
+                for line in wrap_words(&format!("+ {}", feature.benefit), width) {
+                    lines.push(Spans::from(Span::styled(format!("  {}", line), app.theme.positive)));
+                }
+                for line in wrap_words(&format!("- {}", feature.cost), width) {
+                    lines.push(Spans::from(Span::styled(format!("  {}", line), app.theme.negative)));
+                }
+            }
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let heights = items.iter().map(|item| item.height()).collect::<Vec<usize>>();
+    app.screen.add_list(chunks[0], &heights, app.notice_selected, Row::Notice);
+
+    let mut state = ListState::default();
+    if !items.is_empty() {
+        state.select(Some(app.notice_selected));
+    }
+    let list = List::new(items)
+        .highlight_style(app.theme.selected);
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let help = format!(
+        "{} and {} to pick, {} to try it with a tutorial, {} to dismiss. {} shows this again",
+        app.keymap.get_keys(Action::Up),
+        app.keymap.get_keys(Action::Down),
+        app.keymap.get_keys(Action::Select),
+        app.keymap.get_keys(Action::Dismiss),
+        app.keymap.get_keys(Action::WhatsNew)
+    );
+    f.render_widget(Paragraph::new(help).wrap(Wrap { trim: false }), chunks[1]);
+}
+
+fn get_line_starts(text: &Text, width: usize) -> Vec<usize> {
+    // the screen line each line of the text starts on once wrapped,
+    // followed by the height of the whole text
+    let mut starts = vec![0];
+    for line in &text.lines {
+        let height = if line.width() <= width { 1 } else { get_wrapped_height(line, width) };
+        starts.push(starts[starts.len() - 1] + height);
+    }
+
+    starts
+}
+
+fn get_wrapped_height(line: &Spans, width: usize) -> usize {
+    // lets tui wrap the line the way the viewer does, with a marker on
+    // the line after it, and counts the rows above the marker
+    const MARKER: &str = "\u{E000}";
+    let area = Rect::new(0, 0, width as u16, (2 * line.width() / width.max(1) + 2) as u16);
+    let mut buffer = Buffer::empty(area);
+    let text = Text::from(vec![line.clone(), Spans::from(MARKER)]);
+    Paragraph::new(text).wrap(Wrap { trim: false }).render(area, &mut buffer);
+
+    (0..area.height)
+        .position(|y| buffer.get(0, y).symbol == MARKER)
+        .unwrap_or(area.height as usize)
+}
+
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    // breaks text into lines of at most width columns, between words
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.width() + 1 + word.width() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            },
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+fn draw_dashboard<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(0)
+        .constraints(
+            [
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+            ].as_ref(),
+        )
+        .split(area);
+
+    // the series are cached until the ledger or the query changes
+    let account = app
+        .get_selected_account()
+        .and_then(|id| app.company.get_acccount_by_id(&id))
+        .or_else(|| app.company.find_account_by_path(&app.config.assets_account))
+        .map(|account| account.id.clone());
+    app.update_dashboard(account.as_deref());
+    let dashboard = match &app.dashboard {
+        Some(dashboard) => dashboard,
+        None => return,
+    };
+
+    // net worth over time
+    let net_worth = to_points(&dashboard.net_worth);
+    draw_line_chart(f, "Net Worth", &net_worth, app.theme.chart, chunks[0]);
+
+    // the history of the account picked in the chart of accounts
+    let history = account.and_then(|id| dashboard.balances.iter().find(|(charted, _)| *charted == id));
+    match history {
+        Some((id, history)) => {
+            let title = format!(
+                "{} Balance (pick another account with {})",
+                app.company.get_account_path(id), app.keymap.get_keys(Action::Charts)
+            );
+            draw_line_chart(f, &title, &to_points(history), app.theme.chart_alt, chunks[1]);
+        },
+        None => {
+            let empty = Paragraph::new("No accounts to show")
+                .block(Block::default().title("Balance").borders(Borders::ALL));
+            f.render_widget(empty, chunks[1]);
+        },
+    }
+
+    // income against expenses by month
+    let bars = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(0)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[2]);
+
+    let months = &dashboard.months;
+    let labels = months
+        .iter()
+        .map(|(month, _, _)| month.format("%b %y").to_string())
+        .collect::<Vec<String>>();
+    let income = months
+        .iter()
+        .zip(labels.iter())
+        .map(|((_, income, _), label)| (&label[..], income.max(0.0).round() as u64))
+        .collect::<Vec<(&str, u64)>>();
+    let expenses = months
+        .iter()
+        .zip(labels.iter())
+        .map(|((_, _, expense), label)| (&label[..], expense.max(0.0).round() as u64))
+        .collect::<Vec<(&str, u64)>>();
+
+    let income_chart = BarChart::default()
+        .block(Block::default().title("Monthly Income").borders(Borders::ALL))
+        .data(&income)
+        .bar_width(7)
+        .bar_style(app.theme.positive)
+        .value_style(app.theme.bar(app.theme.positive));
+    f.render_widget(income_chart, bars[0]);
+
+    let expense_chart = BarChart::default()
+        .block(Block::default().title("Monthly Expenses").borders(Borders::ALL))
+        .data(&expenses)
+        .bar_width(7)
+        .bar_style(app.theme.negative)
+        .value_style(app.theme.bar(app.theme.negative));
+    f.render_widget(expense_chart, bars[1]);
+}
+
+fn to_points(history: &[(NaiveDate, f64)]) -> Vec<(f64, f64)> {
+    // charts plot days since the first date against the balance
+    let first = match history.first() {
+        Some((date, _)) => *date,
+        None => return Vec::new(),
+    };
+
+    history
+        .iter()
+        .map(|(date, balance)| ((*date - first).num_days() as f64, *balance))
+        .collect()
+}
+
+fn draw_line_chart<B>(f: &mut Frame<B>, title: &str, points: &[(f64, f64)], style: Style, area: Rect)
+where
+    B: Backend,
+{
+    let block = Block::default().title(title.to_string()).borders(Borders::ALL);
+
+    if points.is_empty() {
+        f.render_widget(Paragraph::new("No transactions to chart").block(block), area);
+        return;
+    }
+
+    let last_day = points.iter().map(|(x, _)| *x).fold(1.0, f64::max);
+    let low = points.iter().map(|(_, y)| *y).fold(0.0, f64::min);
+    let high = points.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    let high = if high - low < 1.0 { low + 1.0 } else { high };
+
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(style)
+        .data(points);
+
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(Axis::default()
+            .title("days")
+            .bounds([0.0, last_day])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", last_day))]))
+        .y_axis(Axis::default()
+            .bounds([low, high])
+            .labels(vec![Span::raw(format!("{:.0}", low)), Span::raw(format!("{:.0}", high))]));
+    f.render_widget(chart, area);
+}
+
+fn get_menu_text(app: &mut App) -> Text<'_> {
+    // built from the keymap so rebinding a key also changes the menu
+    let mut text = Text::from("");
+    for (_, keys, description) in app.keymap.get_menu() {
+        text.extend(Text::raw(format!("({}) {}", keys, description)));
+    }
+
     text
 }
 
-fn fill_viewer(app: &mut App) -> Text {
+fn fill_viewer(app: &mut App) -> Text<'_> {
 
     let mut text = Text::from("");
 
     match app.focus {
         Focus::Charts => {
-            text = show_charts(app);
+            // drawn as a tree by draw_chart_tree
         },
         Focus::ExpenseReport => {
             text = show_expense_report(app);
@@ -264,31 +698,146 @@ fn fill_viewer(app: &mut App) -> Text {
         },
         Focus::NewTransaction => {
             text.extend(Text::raw("Enter a new transaction"));
+            text.extend(Text::raw(format!("Press {} and enter: [YYYY-MM-DD] <debit> <credit> <amount> [memo] [payee:<payee>]", app.keymap.get_keys(Action::Edit))));
+            text.extend(Text::raw("such as 2014-11-29 Expenses:Food Assets 12.50 \"lunch out\""));
+            text.extend(Text::raw("Accounts may be a path, a name or an account_id. The date defaults to today"));
+            text.extend(Text::raw("Tab completes account paths, and payees after payee:"));
         },
         Focus::Register => {
-            text.extend(Text::raw("Examine the register for an account"));
+            text = show_register_view(app);
         },
         Focus::DeleteTransaction => {
             text.extend(Text::raw("Deleting Transaction"));
+            text.extend(Text::raw(format!("Press {} and enter the transaction_id to delete", app.keymap.get_keys(Action::Edit))));
+        },
+        Focus::EditTransaction => {
+            let edit = app.keymap.get_keys(Action::Edit);
+            text.extend(Text::raw("Edit a transaction"));
+            text.extend(Text::raw(format!("Press {} and enter: <transaction_id> <date|debit|credit|amount|memo|payee|tag|untag> <value>", edit)));
+            text.extend(Text::raw("such as 12 amount 13.75, 12 memo \"lunch with Sam\" or 12 tag travel"));
+            text.extend(Text::raw(format!("Press {} and enter: <transaction_id> meta <key> [value]", edit)));
+            text.extend(Text::raw("to set a piece of metadata, or without a value to remove it"));
+            text.extend(Text::raw(format!("Press {} and enter: <transaction_id> status <account> [uncleared|pending|cleared]", edit)));
+            text.extend(Text::raw("to set the status of a posting, or without a status to move it on to the next"));
+            text.extend(Text::raw("A reconciled transaction asks for the same edit twice"));
+        },
+        Focus::Budget => {
+            text = show_budget_report(app);
+        },
+        Focus::Upcoming => {
+            text = show_upcoming(app);
+        },
+        Focus::Reconcile => {
+            text = show_reconciliation(app);
+        },
+        Focus::Filter => {
+            text.extend(Text::raw("Query the ledger"));
+            text.extend(Text::raw(format!("Press {} and enter terms joined by and, such as", app.keymap.get_keys(Action::Edit))));
+            text.extend(Text::raw("account:Expenses:Food and amount>50 and date>=2014-11 and memo~restaurant"));
+            text.extend(Text::raw("Fields are account, amount, date, memo, payee, tag and meta"));
+            text.extend(Text::raw("Add sort:date, sort:-amount or limit:10 to order the register"));
+            text.extend(Text::raw("The query filters the balance, budget and group views and fills the register"));
+            text.extend(Text::raw("Enter nothing to clear the query"));
+            if !app.query.is_empty() {
+                text.extend(Text::raw(format!("Current query: {}", app.query.describe())));
+            }
+        },
+        Focus::Groups => {
+            text = show_groups(app);
+        },
+        Focus::CashFlow => {
+            text = show_cash_flow(app);
+        },
+        Focus::Dashboard => {
+            // drawn with chart widgets by draw_dashboard
         },
-        Focus::Nothing => {
-            app.report_gen_done = false;
+        Focus::Sandbox => {
+            text = show_sandbox(app);
         },
+        Focus::WhatsNew => {
+            // drawn as a list by draw_whats_new
+        },
+        Focus::Help => {
+            text = show_help(app);
+        },
+        Focus::Nothing => {},
     }
 
     text
 }
 
-fn show_balance_sheet(app: &mut App) -> Text {
+fn show_sandbox(app: &mut App) -> Text<'_> {
     let mut text = Text::from("");
+    let edit = app.keymap.get_keys(Action::Edit);
+    app.update_sandbox_summary();
+
+    let sandbox = match &app.sandbox {
+        Some(sandbox) => sandbox,
+        None => {
+            text.extend(Text::raw("Sandbox"));
+            text.extend(Text::raw("Try changes on a copy of the ledger and compare the balances before keeping them"));
+            text.extend(Text::raw(format!("Press {} and enter start to open it", edit)));
+            return text;
+        },
+    };
 
-    let balances = app.company.get_balance_summary();
+    text.extend(Text::styled("Sandbox - nothing is saved until it is committed", app.theme.warning));
+    text.extend(Text::raw(format!("Press {} and enter commit, discard or import <path>", edit)));
+    text.extend(Text::raw("Every other view works on the sandbox until then"));
+
+    let summary = match &sandbox.summary {
+        Some(summary) => summary,
+        None => return text,
+    };
+
+    let counts = &summary.counts;
+    if counts.is_empty() {
+        text.extend(Text::raw("No changes yet"));
+    }
+    else {
+        let counts = counts
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect::<Vec<String>>();
+        text.extend(Text::raw(format!("Changes: {}", counts.join(", "))));
+    }
+    text.extend(Text::raw(""));
+
+    let changes = &summary.changes;
+    let width = changes.iter().map(|change| change.path.width()).max().unwrap_or(0).max(7) + 2;
+    text.extend(Text::styled(
+        format!("{:<width$}{:>12}{:>12}{:>12}", "Account", "Original", "Sandbox", "Difference", width = width),
+        app.theme.heading,
+    ));
+
+    for change in changes {
+        let before = change.before.map_or("new".to_string(), |before| app.format_amount(before));
+        let difference = change.get_difference();
+        let difference = if difference.abs() < 0.005 { String::new() } else { app.format_amount(difference) };
+
+        text.extend(Text::from(Spans::from(vec![
+            Span::raw(format!("{:<width$}{:>12}{:>12}", change.path, before, app.format_amount(change.after), width = width)),
+            Span::styled(format!("{:>12}", difference), app.theme.amount(change.get_difference())),
+        ])));
+    }
+
+    text
+}
+
+fn show_balance_sheet(app: &mut App) -> Text<'_> {
+    let mut text = Text::from("");
+
+    let company = app.company.filtered(&app.query.filter);
+    let balances = company.get_balance_summary();
     if balances.is_none() {
         text.extend(Text::raw("No balances to show"));
         return text; 
     }
 
     text.extend(Text::raw("Balance Sheet"));
+    if !app.query.filter.is_empty() {
+        text.extend(Text::raw(format!("Filtered by {}", app.query.filter.describe())));
+    }
     let balances = balances.unwrap();
 
     // first print out the debits
@@ -303,92 +852,388 @@ fn show_balance_sheet(app: &mut App) -> Text {
             let (account, sum) = balance;
             if account.r#type == "d" && x == 0 {
                 if !printed_debits {
-                    text.extend(Text::raw("Debits"));
+                    text.extend(Text::styled("Debits", app.theme.debit));
                     printed_debits = true;
                 }
-                let line = String::from(format!("{}\n    = {:.2}", account.name, sum));
-                text.extend(Text::raw(line));
+                text.extend(Text::raw(account.name.clone()));
+                text.extend(amount_line(app, "    = ", *sum));
                 total_debits += sum; 
             }
             else if account.r#type == "c" && x == 1 {
                 if !printed_credits {
-                    text.extend(Text::raw("Credits"));
+                    text.extend(Text::styled("Credits", app.theme.credit));
                     printed_credits = true;
                 }
-                let line = String::from(format!("{}\n    = {:.2}", account.name, sum));
-                text.extend(Text::raw(line));
+                text.extend(Text::raw(account.name.clone()));
+                text.extend(amount_line(app, "    = ", *sum));
                 total_credits += sum; 
             }
         }
     }
 
-    text.extend(Text::raw(format!("Total Debits = {}", total_debits)));
-    text.extend(Text::raw(format!("Total Cedits = {}", total_credits)));
+    text.extend(amount_line(app, "Total Debits = ", total_debits));
+    text.extend(amount_line(app, "Total Cedits = ", total_credits));
 
     text
 }
 
-fn show_charts(app: &mut App) -> Text {
+fn amount_line<'a>(app: &App, label: &'a str, amount: f64) -> Text<'a> {
+    // a label followed by an amount in the theme's colors
+    Text::from(Spans::from(vec![
+        Span::raw(label),
+        Span::styled(app.format_amount(amount), app.theme.amount(amount)),
+    ]))
+}
+
+fn show_expense_report(app: &mut App) -> Text<'_> {
 
     let mut text = Text::from("");
 
-    let chart = app.company.get_chart_of_accounts();
-    if chart.is_none() {
-        text.extend(Text::raw("No accounts to show"));
-        return text;
-    }
+    let (start, end) = app.company.get_current_period(&app.report_period);
+    let company = app.company.filtered(&app.query.filter);
 
-    text.extend(Text::raw("Chart of Accounts"));
-    let chart = chart.unwrap();
-    for listing in chart {
-        let mut line = String::new();
-        let mut first = true;
-        for account in listing {
-            if first {
-                line = format!("{}", account.name);
-                first = false;
+    match ExpenseReport::generate(&company, start, end) {
+        Ok(report) => {
+            for line in report.to_lines(20, &app.config) {
+                text.extend(Text::raw(line));
             }
-            else {
-                line = format!("{} -> {}", account.name, line );
+            text.extend(Text::raw(""));
+            text.extend(Text::raw(format!("Press {} to change the period", app.keymap.get_keys(Action::CyclePeriod))));
+            text.extend(Text::raw(format!("Press {} and enter csv, html or md, and optionally a path, to export", app.keymap.get_keys(Action::Edit))));
+            text.extend(Text::raw("Enter service, and optionally a format, to have the external report service build it instead"));
+            if app.service_job.is_some() {
+                text.extend(Text::raw(format!("Waiting on the report service, press {} to cancel", app.keymap.get_keys(Action::CancelService))));
             }
+        },
+        Err(err) => text.extend(Text::raw(format!("No expense report to show: {}", err))),
+    }
+
+    if !app.report_path.as_os_str().is_empty() {
+        text.extend(Text::raw(format!("Last report written to {}", app.report_path.display())));
+    }
+
+    text
+}
+
+fn show_budget_report(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+    let edit = app.keymap.get_keys(Action::Edit);
+
+    // report on the same period as the other reports
+    let (start, end) = app.company.get_current_period(&app.report_period);
+
+    let company = app.company.filtered(&app.query.filter);
+    let lines = company.get_budget_report(&app.report_period, start);
+    if lines.is_none() {
+        text.extend(Text::raw("No budgets to show"));
+        text.extend(Text::raw(format!("Press {} and enter set <account> <amount> [month|quarter|year] to add one", edit)));
+        return text;
+    }
+
+    text.extend(Text::raw(format!("Budget vs Actual, {} to {}", app.format_date(start), app.format_date(end))));
+    if !app.query.filter.is_empty() {
+        text.extend(Text::raw(format!("Filtered by {}", app.query.filter.describe())));
+    }
+    text.extend(Text::raw(format!("Press {} to change the period", app.keymap.get_keys(Action::CyclePeriod))));
+    text.extend(Text::raw(format!(
+        "Press {} and enter set <account> <amount> [month|quarter|year] or delete <account> to change a budget", edit
+    )));
+    text.extend(Text::raw(format!("{:<24}{:>10}{:>10}{:>11}{:>7}", "Account", "Actual", "Budget", "Remaining", "Used")));
 
+    for line in lines.unwrap() {
+        let name = format!("{}{}", "  ".repeat(line.depth), line.account.name);
+        let used = match line.percent_used() {
+            Some(percent) => format!("{:.0}%", percent),
+            None => "-".to_string(),
+        };
+        let style = if line.is_overspent() {
+            app.theme.warning
         }
-        text.extend(Text::raw(line));
+        else {
+            Style::default()
+        };
+        let remaining = line.remaining();
+        text.extend(Text::from(Spans::from(vec![
+            Span::styled(format!("{:<24}", name), style),
+            Span::styled(format!("{:>10}", app.format_amount(line.actual)), app.theme.amount(line.actual)),
+            Span::styled(format!("{:>10}", app.format_amount(line.budget)), app.theme.amount(line.budget)),
+            Span::styled(format!("{:>11}", app.format_amount(remaining)), app.theme.amount(remaining)),
+            Span::styled(format!("{:>7}", used), style),
+        ])));
+    }
+
+    text
+}
+
+fn show_upcoming(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+    let usage = format!(
+        "Press {} and enter add <daily|weekly|monthly|yearly> <YYYY-MM-DD> <debit> <credit> <amount> [memo] to schedule a transaction, or delete <id>",
+        app.keymap.get_keys(Action::Edit)
+    );
+
+    if app.company.recurrences.is_empty() {
+        text.extend(Text::raw("No scheduled transactions"));
+        text.extend(Text::raw(usage));
+        return text;
+    }
+
+    let today = Local::today().naive_local();
+    let describe = |recurrence: &Recurrence, date: NaiveDate| {
+        let amount = app.format_amount(recurrence.amount.parse::<f64>().unwrap_or(0.0));
+        format!("{}  {:>3}  {:>10}  {} ({})", app.format_date(date), recurrence.id, amount, recurrence.memo, recurrence.frequency)
     };
 
+    let due = app.company.get_due_recurrences(today);
+    if !due.is_empty() {
+        text.extend(Text::styled(
+            "Due for approval",
+            app.theme.heading,
+        ));
+        for (recurrence, date) in &due {
+            text.extend(Text::raw(describe(recurrence, *date)));
+        }
+        text.extend(Text::raw(format!("Press {} to post all due transactions", app.keymap.get_keys(Action::Approve))));
+        text.extend(Text::raw(""));
+    }
+
+    let upcoming = app.company.get_upcoming_recurrences(today, today + chrono::Duration::days(60));
+    text.extend(Text::styled(
+        "Upcoming in the next 60 days",
+        app.theme.heading,
+    ));
+    if upcoming.is_empty() {
+        text.extend(Text::raw("Nothing scheduled"));
+    }
+    for (recurrence, date) in &upcoming {
+        text.extend(Text::raw(describe(recurrence, *date)));
+    }
+    text.extend(Text::raw(""));
+    text.extend(Text::raw(usage));
+
     text
 }
 
-fn show_expense_report(app: &mut App) -> Text {
-   
+fn show_reconciliation(app: &mut App) -> Text<'_> {
+
     let mut text = Text::from("");
 
-    if !app.report_gen_done {
-        text.extend(Text::raw("Generating Expense Report at"));
-        let res = app.company.generate_expense_report(&app.db_path);
-        if res.is_err() {
-            text.extend(Text::raw("error generating report")); 
+    let reconciliation = match &app.reconciliation {
+        Some(reconciliation) => reconciliation,
+        None => {
+            text.extend(Text::raw("Reconcile an account with a bank statement"));
+            text.extend(Text::raw(format!("Press {} and enter: <account_id> <YYYY-MM-DD> <balance>", app.keymap.get_keys(Action::Edit))));
+            return text;
+        }
+    };
+
+    let account_name = app.company
+        .get_acccount_by_id(&reconciliation.account)
+        .map_or("", |account| &account.name[..]);
+    text.extend(Text::raw(format!(
+        "Reconciling {} to the statement of {}",
+        account_name, app.format_date(reconciliation.statement_date)
+    )));
+    text.extend(Text::raw(format!(
+        "{} to move, {} to tick, {} to finish",
+        [app.keymap.get_keys(Action::Up), app.keymap.get_keys(Action::Down)].join(", "),
+        app.keymap.get_keys(Action::ToggleCleared),
+        app.keymap.get_keys(Action::FinishReconciliation)
+    )));
+    text.extend(Text::raw(""));
+
+    for (idx, transaction) in reconciliation.get_candidates(&app.company).iter().enumerate() {
+        let ticked = if reconciliation.ticked.contains(&transaction.id) { "[x]" } else { "[ ]" };
+        let line = format!(
+            "{} {} {}  {:>10}  {}",
+            if idx == reconciliation.selected { ">" } else { " " },
+            ticked,
+            app.format_date(transaction.date),
+            app.format_amount(transaction.amount.parse::<f64>().unwrap_or(0.0)),
+            transaction.memo
+        );
+
+        let style = if idx == reconciliation.selected {
+            app.theme.selected
         }
         else {
-            let res = res.unwrap();
-            if res.is_none() {
-                text.extend(Text::raw("No Path To Report")); 
-            }
-            else {
-                let path = res.unwrap();
-                app.report_path = path.clone();
-                text.extend(Text::raw(format!("{}", path.display())));
-                text.extend(Text::raw("Locate the expense report at the above path"));
-            }
+            Style::default()
+        };
+        app.screen.lines.push((text.lines.len(), Row::Reconcile(idx)));
+        text.extend(Text::styled(line, style));
+    }
 
+    let difference = reconciliation.get_difference(&app.company);
+    text.extend(Text::raw(""));
+    text.extend(Text::raw(format!("Statement balance = {}", app.format_amount(reconciliation.statement_balance))));
+    text.extend(Text::raw(format!("Cleared balance   = {}", app.format_amount(reconciliation.get_cleared_balance(&app.company)))));
+    text.extend(Text::styled(
+        format!("Difference        = {}", app.format_amount(difference)),
+        if reconciliation.is_balanced(&app.company) {
+            app.theme.positive
         }
-        app.report_gen_done = true;
+        else {
+            app.theme.negative
+        },
+    ));
+
+    text
+}
+
+fn show_groups(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+
+    let company = app.company.filtered(&app.query.filter);
+    if !app.query.filter.is_empty() {
+        text.extend(Text::raw(format!("Filtered by {}", app.query.filter.describe())));
+    }
+
+    let mut sections = vec![
+        ("By Payee".to_string(), company.group_by_payee()),
+        ("By Tag".to_string(), company.group_by_tag()),
+    ];
+    for key in company.get_metadata_keys() {
+        let groups = company.group_by_metadata(&key);
+        sections.push((format!("By {}", key), groups));
+    }
+
+    for (title, groups) in sections {
+        if groups.is_empty() {
+            continue;
+        }
+
+        text.extend(Text::styled(title, app.theme.heading));
+        for (key, total, count) in groups {
+            text.extend(Text::from(Spans::from(vec![
+                Span::raw(format!("{:<24}", key)),
+                Span::styled(format!("{:>12}", app.format_amount(total)), app.theme.amount(total)),
+                Span::raw(format!("{:>6}", count)),
+            ])));
+        }
+        text.extend(Text::raw(""));
+    }
+
+    text
+}
+
+fn show_help(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+
+    if let Some(topic) = app.help_topic {
+        text.extend(Text::styled(topic.title, app.theme.heading));
+        text.extend(Text::raw(""));
+        for line in manual::get_body(topic, &app.keymap) {
+            text.extend(Text::raw(line));
+        }
+        text.extend(Text::raw(""));
+        text.extend(Text::raw(format!(
+            "{} for every topic, {} to close the manual",
+            app.keymap.get_keys(Action::Dismiss), app.keymap.get_keys(Action::Help)
+        )));
+        return text;
+    }
+
+    if app.help_search.is_empty() {
+        text.extend(Text::styled("Manual", app.theme.heading));
     }
     else {
-        text.extend(Text::raw("Path to report is ..."));
-        let path = app.report_path.clone();
-        text.extend(Text::raw(format!("{}", path.display())));
+        text.extend(Text::styled(format!("Topics about {}", app.help_search), app.theme.heading));
+    }
+    text.extend(Text::raw(format!(
+        "{} to read the picked topic, {} and enter words to search, {} to close",
+        app.keymap.get_keys(Action::Select), app.keymap.get_keys(Action::Edit), app.keymap.get_keys(Action::Dismiss)
+    )));
+    text.extend(Text::raw(""));
+
+    for (idx, topic) in app.get_help_matches().iter().enumerate() {
+        let line = format!("{:<14}{}", topic.name, topic.title);
+        app.screen.lines.push((text.lines.len(), Row::Topic(idx)));
+        if idx == app.help_selected {
+            text.extend(Text::styled(line, app.theme.selected));
+        }
+        else {
+            text.extend(Text::raw(line));
+        }
+    }
+
+    text
+}
+
+fn show_register_view(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+
+    if app.query.is_empty() {
+        text.extend(Text::raw("Examine the register for an account"));
+        text.extend(Text::raw(format!("Press {} and enter an account such as Expenses:Food or a query", app.keymap.get_keys(Action::Edit))));
+        return text;
+    }
+
+    text.extend(Text::raw(format!("Register for {}", app.query.describe())));
+
+    for (transaction, amount, total) in app.query.run_register(&app.company) {
+        let debit = app.company.get_account_path(&transaction.debit);
+        let credit = app.company.get_account_path(&transaction.credit);
+        let mut entry = Text::from(Spans::from(vec![
+            Span::raw(format!("{} {:>4} {:<20} ", app.format_date(transaction.date), transaction.id, transaction.memo)),
+            Span::styled(format!("{:>10}", app.format_amount(amount)), app.theme.amount(amount)),
+            Span::raw(" "),
+            Span::styled(format!("{:>10}", app.format_amount(total)), app.theme.amount(total)),
+        ]));
+        entry.extend(Text::from(Spans::from(vec![
+            Span::raw("    "),
+            Span::styled(debit, app.theme.debit),
+            Span::raw(" <- "),
+            Span::styled(credit, app.theme.credit),
+        ])));
+
+        // the transaction picked with the mouse is highlighted
+        if app.register_selected.as_ref() == Some(&transaction.id) {
+            entry.patch_style(app.theme.selected);
+        }
+        for line in 0..entry.lines.len() {
+            app.screen.lines.push((text.lines.len() + line, Row::Transaction(transaction.id.clone())));
+        }
+        text.extend(entry);
+    }
+
+    text
+}
+
+fn show_cash_flow(app: &mut App) -> Text<'_> {
+
+    let mut text = Text::from("");
+
+    let (start, end) = app.company.get_current_period(&app.report_period);
+    let company = app.company.filtered(&app.query.filter);
+
+    match CashFlow::generate(&company, start, end) {
+        Ok(cash_flow) => {
+            text.extend(Text::raw(format!(
+                "Cash Flow Statement, {} to {}",
+                app.format_date(cash_flow.start), app.format_date(cash_flow.end)
+            )));
+            for (label, amount) in cash_flow.to_rows() {
+                match amount {
+                    Some(amount) => text.extend(Text::from(Spans::from(vec![
+                        Span::raw(label),
+                        Span::styled(format!("{:>12}", app.format_amount(amount)), app.theme.amount(amount)),
+                    ]))),
+                    None => text.extend(Text::raw(label)),
+                }
+            }
+            text.extend(Text::raw(""));
+            text.extend(Text::raw(format!("Press {} to change the period", app.keymap.get_keys(Action::CyclePeriod))));
+        },
+        Err(err) => {
+            text.extend(Text::raw(format!("No cash flow to show: {}", err)));
+            text.extend(Text::raw("Mark cash accounts from the chart of accounts with: cash <account_id> on"));
+        },
     }
 
     text
-}
\ No newline at end of file
+}
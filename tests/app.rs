@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use money::app::App;
+use money::company::Company;
+
+fn load(name: &str) -> (App, PathBuf) {
+    let path = std::env::temp_dir().join(format!("money-{}-{}.json", name, std::process::id()));
+    std::fs::copy("example.json", &path).unwrap();
+    let app = App::default().database(&path).load_company().unwrap();
+    (app, path)
+}
+
+fn change_elsewhere(path: &PathBuf, change: impl FnOnce(&mut Company)) {
+    // as money serve would, and late enough for the file to look newer
+    thread::sleep(Duration::from_millis(20));
+    let mut company = Company::default().load(path).unwrap();
+    change(&mut company);
+    company.write_to(path.to_str().unwrap()).unwrap();
+}
+
+fn name(path: &PathBuf, account_id: &str) -> String {
+    Company::default().load(path).unwrap().get_acccount_by_id(account_id).unwrap().name.clone()
+}
+
+#[test]
+fn a_change_saved_elsewhere_is_read_before_the_next_one() {
+    let (mut app, path) = load("reload");
+    change_elsewhere(&path, |company| company.rename_account("8", "Lodging").unwrap());
+
+    app.change_account("rename 9 Groceries");
+    assert_eq!(name(&path, "8"), "Lodging");
+    assert_eq!(name(&path, "9"), "Groceries");
+
+    // undo only goes back as far as the reload
+    app.undo();
+    app.undo();
+    assert_eq!(app.messages.last().unwrap(), "Nothing to undo");
+    assert_eq!(name(&path, "8"), "Lodging");
+    assert_eq!(name(&path, "9"), "Food");
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn a_save_never_writes_over_a_change_made_elsewhere() {
+    let (mut app, path) = load("overwrite");
+    change_elsewhere(&path, |company| company.rename_account("8", "Lodging").unwrap());
+
+    app.company.rename_account("9", "Groceries").unwrap();
+    assert!(app.save_company().is_err());
+    assert_eq!(name(&path, "8"), "Lodging");
+    assert_eq!(name(&path, "9"), "Food");
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,63 @@
+use chrono::NaiveDate;
+use money::budget::{period_bounds, Budget};
+use money::company::Company;
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+#[test]
+fn periods_are_calendar_aligned() {
+    assert_eq!(period_bounds("month", date("2024-02-10")), (date("2024-02-01"), date("2024-02-29")));
+    assert_eq!(period_bounds("quarter", date("2014-11-28")), (date("2014-10-01"), date("2014-12-31")));
+    assert_eq!(period_bounds("year", date("2014-11-28")), (date("2014-01-01"), date("2014-12-31")));
+    assert_eq!(period_bounds("fortnight", date("2014-12-05")), (date("2014-12-01"), date("2014-12-31")));
+}
+
+#[test]
+fn budgets_scale_to_the_period_reported() {
+    let mut budget = Budget::new();
+    budget.set_amount("1200").set_period("year");
+    assert_eq!(budget.get_amount_for_period("month"), 100.0);
+    assert_eq!(budget.get_amount_for_period("quarter"), 300.0);
+    budget.set_period("week");
+    assert_eq!(budget.period, "year");
+}
+
+#[test]
+fn the_report_rolls_budgets_and_spending_up_the_tree() {
+    let company = load();
+    let lines = company.get_budget_report("month", date("2014-11-28")).unwrap();
+    let line = |name: &str| lines.iter().find(|line| line.account.name == name).unwrap();
+
+    // only Expenses and the two budgeted accounts under it are reported
+    assert_eq!(lines.len(), 3);
+    assert_eq!(line("Food").actual, 161.0);
+    assert_eq!(line("Food").budget, 400.0);
+    assert_eq!(line("Food").remaining(), 239.0);
+    assert!(!line("Food").is_overspent());
+    // example.json lists the rent overpayment under Assets only
+    assert_eq!(line("Rent").actual, 1200.0);
+    assert_eq!(line("Expenses").budget, 1600.0);
+    assert!(line("Expenses").is_overspent());
+
+    // nothing was spent the month after
+    let lines = company.get_budget_report("month", date("2014-12-01")).unwrap();
+    assert!(lines.iter().all(|line| line.actual == 0.0 && line.percent_used() == Some(0.0)));
+}
+
+#[test]
+fn a_new_budget_replaces_the_old_one() {
+    let mut company = load();
+    let mut budget = Budget::new();
+    budget.set_id_in_company(&mut company).set_account("9").set_amount("500");
+    company.insert_budget(budget);
+
+    assert_eq!(company.budgets.len(), 2);
+    assert_eq!(company.get_budget_by_account("9").unwrap().get_amount(), 500.0);
+    assert!(Company::default().get_budget_report("month", date("2014-11-28")).is_none());
+}
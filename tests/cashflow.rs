@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use money::cashflow::CashFlow;
+use money::company::Company;
+use money::transaction::Transaction;
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+fn november(company: &Company) -> CashFlow {
+    CashFlow::generate(company, date("2014-11-01"), date("2014-11-30")).unwrap()
+}
+
+#[test]
+fn cash_moves_by_counter_account() {
+    let company = load();
+    let cashflow = november(&company);
+    assert_eq!(cashflow.opening, 0.0);
+    assert_eq!(cashflow.get_total_inflow(), 224.0);
+    assert_eq!(cashflow.get_total_outflow(), 12083.0);
+    assert_eq!(cashflow.get_closing(), -11859.0);
+
+    let rent = cashflow.lines.iter().find(|line| line.account == "Expenses:Rent").unwrap();
+    assert_eq!((rent.inflow, rent.outflow), (125.0, 1200.0));
+
+    // December opens where November closed
+    let december = CashFlow::generate(&company, date("2014-12-01"), date("2014-12-31")).unwrap();
+    assert_eq!(december.opening, -11859.0);
+    assert!(december.lines.is_empty());
+}
+
+#[test]
+fn lines_take_the_nearest_category() {
+    let mut company = load();
+    company.set_account_category("5", "financing").unwrap();
+    company.set_account_category("8", "investing").unwrap();
+    assert!(company.set_account_category("9", "leisure").is_err());
+
+    let cashflow = november(&company);
+    assert_eq!(cashflow.get_net("financing"), 99.0);
+    assert_eq!(cashflow.get_net("investing"), -1075.0);
+    assert_eq!(cashflow.get_net("operating"), -10883.0);
+}
+
+#[test]
+fn transfers_between_cash_accounts_are_left_out() {
+    let mut company = load();
+    let savings = company.create_account("1", "Savings", "d").unwrap();
+    let mut transaction = Transaction::new();
+    transaction.set_debit(&savings).set_credit("1").set_amount("500").set_memo("transfer");
+    transaction.date = date("2014-11-28");
+    company.post_transaction(transaction);
+
+    assert_eq!(november(&company).get_closing(), -11859.0);
+}
+
+#[test]
+fn only_debit_accounts_hold_cash() {
+    let mut company = load();
+    assert!(company.set_account_cash("5", true).is_err());
+    company.set_account_cash("1", false).unwrap();
+    assert!(CashFlow::generate(&company, date("2014-11-01"), date("2014-11-30")).is_err());
+}
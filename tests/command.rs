@@ -0,0 +1,79 @@
+use money::app::{App, Focus};
+use money::command;
+use money::company::Company;
+
+fn load() -> App {
+    App {
+        company: Company::default().load("example.json").expect("example.json loads"),
+        ..App::default()
+    }
+}
+
+fn complete(app: &mut App, input: &str) -> String {
+    app.input = input.to_string();
+    command::complete(app);
+    app.input.clone()
+}
+
+#[test]
+fn command_names_complete_after_a_colon() {
+    let mut app = load();
+    assert_eq!(complete(&mut app, ":re"), ":re");
+    assert_eq!(app.messages.last().unwrap(), "Matches: reg, redo");
+    assert_eq!(complete(&mut app, ":bo"), ":bo");
+
+    // without the colon the input belongs to the view, not the command line
+    app.focus = Focus::BalanceSheet;
+    assert_eq!(complete(&mut app, "his"), "his");
+    assert_eq!(complete(&mut app, ":his"), ":history ");
+}
+
+#[test]
+fn account_paths_complete_by_any_part() {
+    let mut app = load();
+    assert_eq!(complete(&mut app, ":reg foo"), ":reg Expenses:Food ");
+    assert_eq!(complete(&mut app, ":add 2014-11-29 Expenses:R"), ":add 2014-11-29 Expenses:Rent ");
+
+    // several matches complete as far as they agree
+    assert_eq!(complete(&mut app, ":reg Expenses:"), ":reg Expenses:");
+    assert!(app.messages.last().unwrap().starts_with("Matches: Expenses:Food"));
+    assert_eq!(complete(&mut app, ":reg Ex"), ":reg Expenses");
+}
+
+#[test]
+fn topics_and_periods_complete() {
+    let mut app = load();
+    assert_eq!(complete(&mut app, ":help recon"), ":help reconcile ");
+    assert_eq!(complete(&mut app, ":bal --period q"), ":bal --period quarter ");
+    assert_eq!(complete(&mut app, ":bal -p y"), ":bal -p year ");
+}
+
+#[test]
+fn only_known_commands_are_remembered() {
+    let mut app = load();
+    command::run(&mut app, ":commands");
+    command::run(&mut app, ":comands");
+    command::run(&mut app, ":bal");
+    assert_eq!(app.state.command_history, ["commands", "bal"]);
+    assert_eq!(app.messages.iter().filter(|message| message.contains("is not a command")).count(), 1);
+
+    // and Up brings them back on the command line only
+    app.input = "Food".to_string();
+    command::recall(&mut app, true);
+    assert_eq!(app.input, "Food");
+    app.input = ":".to_string();
+    command::recall(&mut app, true);
+    assert_eq!(app.input, ":bal");
+    command::recall(&mut app, true);
+    assert_eq!(app.input, ":commands");
+}
+
+#[test]
+fn a_view_without_an_input_runs_nothing_without_a_colon() {
+    let mut app = load();
+    app.focus = Focus::BalanceSheet;
+    app.input = "commands".to_string();
+    app.submit_input();
+    assert!(app.state.command_history.is_empty());
+    assert!(app.messages.last().unwrap().starts_with("This view takes no input"));
+}
@@ -0,0 +1,119 @@
+use chrono::NaiveDate;
+use money::company::Company;
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+#[test]
+fn moving_a_posting_clears_its_status() {
+    let mut company = load();
+    let mut transaction = company.get_transaction_by_id("6").cloned().unwrap();
+    transaction.set_status("9", "reconciled").set_status("1", "cleared");
+    company.update_transaction(transaction, true).unwrap();
+
+    // taco bell moves from Food to Rent, and only that side starts over
+    let mut transaction = company.get_transaction_by_id("6").cloned().unwrap();
+    transaction.set_debit("8");
+    company.update_transaction(transaction, true).unwrap();
+
+    let transaction = company.get_transaction_by_id("6").unwrap();
+    assert_eq!(transaction.get_status("8"), "uncleared");
+    assert_eq!(transaction.get_status("1"), "cleared");
+    assert!(!transaction.is_reconciled());
+}
+
+#[test]
+fn an_edit_cannot_post_to_one_account_twice() {
+    let mut company = load();
+    let mut transaction = company.get_transaction_by_id("6").cloned().unwrap();
+    transaction.set_credit("9");
+
+    assert!(company.update_transaction(transaction, false).is_err());
+    assert_eq!(company.get_transaction_by_id("6").unwrap().credit, "1");
+}
+
+#[test]
+fn tags_are_removed_whatever_their_case() {
+    let mut transaction = load().get_transaction_by_id("6").cloned().unwrap();
+    transaction.add_tag("Lunch");
+
+    assert!(transaction.remove_tag("#lunch").is_ok());
+    assert!(transaction.tags.is_empty());
+    assert!(transaction.remove_tag("lunch").is_err());
+}
+
+#[test]
+fn a_merge_that_would_clash_changes_nothing() {
+    let mut company = load();
+    company.create_account("9", "Lunch", "d").unwrap();
+    company.create_account("8", "Lunch", "d").unwrap();
+    let before = serde_json::to_string(&company).unwrap();
+
+    // Food's Lunch cannot join Rent's, so Food keeps its transactions too
+    assert!(company.merge_account("9", "8").is_err());
+    assert_eq!(serde_json::to_string(&company).unwrap(), before);
+}
+
+#[test]
+fn accounts_are_renamed_unless_a_sibling_has_the_name() {
+    let mut company = load();
+    company.rename_account("9", " Groceries ").unwrap();
+    assert_eq!(company.get_acccount_by_id("9").unwrap().name, "Groceries");
+
+    assert!(company.rename_account("9", "rent").is_err());
+    assert!(company.rename_account("9", "Food:Lunch").is_err());
+    assert!(company.rename_account("9", " ").is_err());
+    assert_eq!(company.get_acccount_by_id("9").unwrap().name, "Groceries");
+}
+
+#[test]
+fn a_moved_account_keeps_its_parent_in_step() {
+    let mut company = load();
+    company.move_account("9", "8").unwrap();
+    assert_eq!(company.get_acccount_by_id("9").unwrap().parent, "8");
+    assert!(company.get_acccount_by_id("8").unwrap().subaccounts.contains(&"9".to_string()));
+    assert!(!company.get_acccount_by_id("2").unwrap().subaccounts.contains(&"9".to_string()));
+
+    // not under itself and not across the ledger
+    assert!(company.move_account("8", "9").is_err());
+    assert!(company.move_account("9", "5").is_err());
+    assert_eq!(company.get_acccount_by_id("9").unwrap().parent, "8");
+}
+
+#[test]
+fn a_merged_account_hands_over_its_postings() {
+    let mut company = load();
+    company.merge_account("9", "8").unwrap();
+    assert!(company.get_acccount_by_id("9").is_none());
+    for id in ["6", "7", "8"] {
+        assert_eq!(company.get_transaction_by_id(id).unwrap().debit, "8");
+    }
+    assert!(company.get_acccount_by_id("8").unwrap().transactions.contains(&"6".to_string()));
+
+    // Rent pays Assets in transaction 11, so they cannot become one account
+    assert!(company.merge_account("8", "1").is_err());
+}
+
+#[test]
+fn closing_an_account_closes_what_is_under_it() {
+    let mut company = load();
+    company.close_account("2").unwrap();
+    assert!(company.get_acccount_by_id("9").unwrap().closed);
+    assert!(company.close_account("9").is_err());
+
+    // reopening a subaccount reopens the parents it needs
+    company.reopen_account("9").unwrap();
+    assert!(!company.get_acccount_by_id("2").unwrap().closed);
+    assert!(company.get_acccount_by_id("8").unwrap().closed);
+    assert!(company.reopen_account("9").is_err());
+}
+
+#[test]
+fn income_and_expenses_come_from_the_accounts_named() {
+    let company = load();
+    let november = NaiveDate::from_ymd(2014, 11, 1);
+    assert_eq!(company.get_monthly_income_expense("Income", "Expenses"), [(november, 99.0, 12083.0)]);
+    assert_eq!(company.get_monthly_income_expense("Income", "Expenses:Food"), [(november, 99.0, 161.0)]);
+    assert_eq!(company.get_monthly_income_expense("Revenue", "Expenses:Food"), [(november, 0.0, 161.0)]);
+}
@@ -0,0 +1,54 @@
+use clap::Parser;
+use money::config::{Args, Config};
+use std::env;
+use std::fs;
+
+// the settings come from the process environment, so every case runs in
+// this one test rather than racing each other
+#[test]
+fn file_then_env_then_flags() {
+    let path = env::temp_dir().join(format!("money-config-{}.toml", std::process::id()));
+    fs::write(&path, "date_format = \"%d/%m/%Y\"\ncurrency = \"$\"\ntick_rate = 100\ntheme = \"dark\"\n").unwrap();
+    let path = path.display().to_string();
+    for name in ["MONEY_CONFIG", "MONEY_DATE_FORMAT", "MONEY_CURRENCY", "MONEY_TICK_RATE", "MONEY_THEME", "NO_COLOR"] {
+        env::remove_var(name);
+    }
+
+    // the file over the defaults
+    let config = Config::from_args(&Args::parse_from(["money", "--config", &path])).unwrap();
+    assert_eq!(config.date_format, "%d/%m/%Y");
+    assert_eq!(config.currency, "$");
+    assert_eq!(config.tick_rate.as_millis(), 100);
+    assert_eq!(config.theme, "dark");
+    assert_eq!(config.database.display().to_string(), "data/db.json");
+
+    // the environment over the file
+    env::set_var("MONEY_CURRENCY", "€");
+    env::set_var("MONEY_TICK_RATE", "50");
+    let config = Config::from_args(&Args::parse_from(["money", "--config", &path])).unwrap();
+    assert_eq!(config.currency, "€");
+    assert_eq!(config.tick_rate.as_millis(), 50);
+    assert_eq!(config.date_format, "%d/%m/%Y");
+
+    // flags over both
+    let args = Args::parse_from(["money", "--config", &path, "--currency", "£", "--date-format", "%Y", "--theme", "light"]);
+    let config = Config::from_args(&args).unwrap();
+    assert_eq!(config.currency, "£");
+    assert_eq!(config.date_format, "%Y");
+    assert_eq!(config.theme, "light");
+    assert_eq!(config.tick_rate.as_millis(), 50);
+
+    // the file may also be named by the environment
+    env::set_var("MONEY_CONFIG", &path);
+    let config = Config::from_args(&Args::parse_from(["money"])).unwrap();
+    assert_eq!(config.date_format, "%d/%m/%Y");
+
+    // and a bad value in the environment is an error
+    env::set_var("MONEY_TICK_RATE", "fast");
+    assert!(Config::from_args(&Args::parse_from(["money"])).is_err());
+
+    for name in ["MONEY_CONFIG", "MONEY_CURRENCY", "MONEY_TICK_RATE"] {
+        env::remove_var(name);
+    }
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+use money::budget::Budget;
+use money::company::Company;
+use money::events::diff;
+use money::recurrence::Recurrence;
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+fn kinds(before: &Company, after: &Company) -> Vec<String> {
+    diff(before, after).into_iter().map(|(kind, _)| kind).collect()
+}
+
+#[test]
+fn no_changes_give_no_events() {
+    let company = load();
+    assert!(diff(&company, &company.clone()).is_empty());
+}
+
+#[test]
+fn transactions_are_added_changed_and_deleted() {
+    let before = load();
+    let mut after = before.clone();
+    after.transactions.retain(|transaction| transaction.id != "6");
+    for transaction in after.transactions.iter_mut().filter(|transaction| transaction.id == "7") {
+        transaction.set_memo("groceries");
+    }
+
+    let changes = diff(&before, &after);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].0, "transaction_changed");
+    assert_eq!(changes[0].1["memo"], "groceries");
+    assert_eq!(changes[1].0, "transaction_deleted");
+    assert_eq!(changes[1].1["id"], "6");
+
+    assert_eq!(kinds(&after, &before), ["transaction_added", "transaction_changed"]);
+}
+
+#[test]
+fn accounts_are_diffed() {
+    let before = load();
+    let mut after = before.clone();
+    after.rename_account("9", "Groceries").unwrap();
+
+    let changes = diff(&before, &after);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, "account_changed");
+    assert_eq!(changes[0].1["name"], "Groceries");
+}
+
+#[test]
+fn budgets_are_diffed() {
+    let before = load();
+    let mut after = before.clone();
+    let mut budget = Budget::new();
+    budget
+        .set_id_in_company(&mut after)
+        .set_account("7")
+        .set_amount("300")
+        .set_period("month");
+    after.insert_budget(budget);
+    after.delete_budget("1");
+
+    assert_eq!(kinds(&before, &after), ["budget_added", "budget_deleted"]);
+}
+
+#[test]
+fn recurrences_are_diffed() {
+    let before = load();
+    let mut after = before.clone();
+    let mut recurrence = Recurrence::new();
+    recurrence
+        .set_id_in_company(&mut after)
+        .set_frequency("monthly")
+        .set_start(NaiveDate::from_ymd(2014, 12, 1))
+        .set_template("8", "1", "1200", "rent");
+    after.recurrences.push(recurrence);
+
+    let changes = diff(&before, &after);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, "recurrence_added");
+    assert_eq!(changes[0].1["memo"], "rent");
+
+    let mut later = after.clone();
+    later.recurrences[0].set_end(Some(NaiveDate::from_ymd(2015, 6, 30)));
+    assert_eq!(kinds(&after, &later), ["recurrence_changed"]);
+}
@@ -0,0 +1,55 @@
+use money::app::App;
+use money::company::Company;
+use money::history::History;
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+fn name(company: &Company, account_id: &str) -> String {
+    company.get_acccount_by_id(account_id).unwrap().name.clone()
+}
+
+#[test]
+fn undo_and_redo_swap_snapshots() {
+    let mut company = load();
+    let mut history = History::new();
+    history.record("rename Food", &company);
+    company.rename_account("9", "Groceries").unwrap();
+    history.confirm();
+
+    assert_eq!(history.undo(&mut company).as_deref(), Some("rename Food"));
+    assert_eq!(name(&company, "9"), "Food");
+    assert_eq!(history.redo(&mut company).as_deref(), Some("rename Food"));
+    assert_eq!(name(&company, "9"), "Groceries");
+    assert!(history.redo(&mut company).is_none());
+}
+
+#[test]
+fn a_failed_commit_keeps_what_can_be_redone() {
+    let path = std::env::temp_dir().join(format!("money-history-{}.json", std::process::id()));
+    let mut app = App {
+        company: load(),
+        db_path: path.clone(),
+        ..App::default()
+    };
+    app.change_account("rename 9 Groceries");
+    app.undo();
+    assert_eq!(name(&app.company, "9"), "Food");
+
+    // Rent cannot be renamed to a sibling's name, and Groceries can still come back
+    app.change_account("rename 8 Insurance");
+    assert!(app.messages.last().unwrap().starts_with("Could not rename an account"));
+    assert_eq!(name(&app.company, "8"), "Rent");
+    app.redo();
+    assert_eq!(name(&app.company, "9"), "Groceries");
+
+    // a change that goes through clears it
+    app.undo();
+    app.change_account("rename 8 Housing");
+    app.redo();
+    assert_eq!(app.messages.last().unwrap(), "Nothing to redo");
+    assert_eq!(name(&app.company, "9"), "Food");
+
+    std::fs::remove_file(path).ok();
+}
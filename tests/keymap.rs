@@ -0,0 +1,83 @@
+use money::keymap::{Action, Key, Keymap};
+use std::collections::BTreeMap;
+
+fn press(keymap: &mut Keymap, keys: &str) -> Vec<Option<Action>> {
+    // every action the keys trigger, as if every action were available
+    keys.split_whitespace()
+        .map(|key| keymap.press(Key::parse(key).unwrap(), |_| true))
+        .collect()
+}
+
+fn from(entries: &[(&str, &str)]) -> Keymap {
+    let settings = entries
+        .iter()
+        .map(|(action, keys)| (action.to_string(), keys.to_string()))
+        .collect::<BTreeMap<String, String>>();
+
+    Keymap::from_settings(&settings).unwrap()
+}
+
+#[test]
+fn single_keys() {
+    let mut keymap = Keymap::default();
+    assert_eq!(press(&mut keymap, "b"), [Some(Action::BalanceSheet)]);
+    assert_eq!(press(&mut keymap, "U"), [Some(Action::Redo)]);
+    assert!(!keymap.is_pending());
+}
+
+#[test]
+fn a_chord_waits_for_its_last_key() {
+    let mut keymap = Keymap::default();
+    assert_eq!(press(&mut keymap, "Z"), [None]);
+    assert!(keymap.is_pending());
+    assert_eq!(keymap.get_pending(), "Z");
+
+    assert_eq!(press(&mut keymap, "Z"), [Some(Action::Quit)]);
+    assert!(!keymap.is_pending());
+}
+
+#[test]
+fn a_broken_chord_counts_its_last_key() {
+    let mut keymap = Keymap::default();
+    assert_eq!(press(&mut keymap, "Z b"), [None, Some(Action::BalanceSheet)]);
+    assert!(!keymap.is_pending());
+
+    assert_eq!(press(&mut keymap, "Z x"), [None, Some(Action::CancelService)]);
+    assert_eq!(press(&mut keymap, "Z Esc"), [None, Some(Action::Dismiss)]);
+}
+
+#[test]
+fn settings_replace_the_default_keys() {
+    let mut keymap = from(&[("charts", "l, g l"), ("undo", "ctrl-z")]);
+    assert_eq!(keymap.get_keys(Action::Charts), "l/g l");
+    assert_eq!(keymap.get_keys(Action::Undo), "ctrl-z");
+
+    assert_eq!(press(&mut keymap, "u"), [None]);
+    assert_eq!(press(&mut keymap, "ctrl-z"), [Some(Action::Undo)]);
+    assert_eq!(press(&mut keymap, "g l"), [None, Some(Action::Charts)]);
+    assert_eq!(press(&mut keymap, "l"), [Some(Action::Charts)]);
+}
+
+#[test]
+fn a_view_action_wins_over_a_global_one() {
+    let mut keymap = Keymap::default();
+    let approve = Key::parse("a").unwrap();
+
+    assert_eq!(keymap.press(approve, |action| action != Action::AddAccount), Some(Action::Approve));
+    assert_eq!(keymap.press(approve, |action| action.is_global()), None);
+}
+
+#[test]
+fn bad_settings_are_refused() {
+    for (action, keys) in [("launch", "x"), ("undo", ""), ("undo", "ctrl-"), ("undo", "F13"), ("undo", "hyper-z")] {
+        let settings = BTreeMap::from([(action.to_string(), keys.to_string())]);
+        assert!(Keymap::from_settings(&settings).is_err(), "{} = {:?} should be refused", action, keys);
+    }
+}
+
+#[test]
+fn keys_read_back_as_written() {
+    for text in ["a", "Enter", "ctrl-r", "alt-x", "F5", "Space", "Comma", "PageDown"] {
+        assert_eq!(Key::parse(text).unwrap().describe(), text);
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::NaiveDate;
+use money::protocol::{Artifact, ReportParams, Request, Response, VERSION};
+use std::fs;
+
+fn database() -> String {
+    fs::read_to_string("example.json").expect("example.json reads")
+}
+
+fn params() -> ReportParams {
+    ReportParams {
+        period: "month".to_string(),
+        start: NaiveDate::from_ymd_opt(2014, 11, 1),
+        end: NaiveDate::from_ymd_opt(2014, 11, 30),
+        accounts: vec!["9".to_string()],
+        format: "csv".to_string(),
+    }
+}
+
+#[test]
+fn request_round_trips() {
+    let request = Request::expense_report(&database(), params()).unwrap();
+    let payload = request.to_payload(false).unwrap();
+    let parsed = Request::parse(&payload).unwrap();
+
+    assert_eq!(parsed.version, VERSION);
+    assert!(!parsed.is_legacy());
+    assert_eq!(parsed.r#type, "expense_report");
+    assert_eq!(parsed.params.start, params().start);
+    assert_eq!(parsed.params.end, params().end);
+    assert_eq!(parsed.params.accounts, ["9"]);
+    assert_eq!(parsed.params.format, "csv");
+    assert_eq!(parsed.database, request.database);
+}
+
+#[test]
+fn legacy_payload_is_the_database_as_read() {
+    let database = database();
+    let request = Request::expense_report(&database, params()).unwrap();
+    assert_eq!(request.to_payload(true).unwrap(), database);
+
+    // and a service reads it back as a bare database
+    let parsed = Request::parse(&database).unwrap();
+    assert!(parsed.is_legacy());
+    assert_eq!(parsed.params.format, ReportParams::default().format);
+    assert_eq!(parsed.database, request.database);
+}
+
+#[test]
+fn response_round_trips() {
+    let response = Response::ok(Artifact {
+        path: Some("/tmp/expense-report.csv".to_string()),
+        content: None,
+        format: "csv".to_string(),
+    });
+    let reply = serde_json::to_string(&response).unwrap();
+    let artifact = Response::parse(&reply).unwrap().into_artifact().unwrap();
+    assert_eq!(artifact.path.as_deref(), Some("/tmp/expense-report.csv"));
+    assert_eq!(artifact.format, "csv");
+
+    let reply = serde_json::to_string(&Response::error("bad_request", "no database")).unwrap();
+    let err = Response::parse(&reply).unwrap().into_artifact().unwrap_err();
+    assert_eq!(err.to_string(), "bad_request: no database");
+}
+
+#[test]
+fn bare_path_replies_are_read_as_artifacts() {
+    let artifact = Response::parse("C:\\reports\\expense-report.html\n").unwrap().into_artifact().unwrap();
+    assert_eq!(artifact.path.as_deref(), Some("C:\\reports\\expense-report.html"));
+
+    assert!(Response::parse("").is_err());
+    assert!(Response::parse("{not json").is_err());
+}
+
+#[test]
+fn newer_responses_are_refused() {
+    let mut response = Response::data(serde_json::json!({}));
+    response.version = VERSION + 1;
+    let reply = serde_json::to_string(&response).unwrap();
+    assert!(Response::parse(&reply).is_err());
+}
+
+#[test]
+fn inline_content_is_saved_only_in_known_formats() {
+    let directory = std::env::temp_dir().join(format!("money-protocol-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+
+    let artifact = Artifact {
+        path: None,
+        content: Some("account,amount\n".to_string()),
+        format: "csv".to_string(),
+    };
+    let path = artifact.save(&directory).unwrap();
+    assert_eq!(path, directory.join("expense-report.csv"));
+    assert_eq!(fs::read_to_string(&path).unwrap().trim_end(), "account,amount");
+
+    for format in ["../../escape", "csv/..", "exe"] {
+        let artifact = Artifact { format: format.to_string(), ..artifact.clone() };
+        assert!(artifact.save(&directory).is_err(), "{} should be refused", format);
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
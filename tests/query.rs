@@ -0,0 +1,131 @@
+use money::company::Company;
+use money::query::{split_words, tokenize, Query};
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+fn ids(company: &Company, input: &str) -> Vec<String> {
+    let query = Query::parse(company, input).expect("the query parses");
+    let mut ids = query
+        .run(company)
+        .iter()
+        .map(|transaction| transaction.id.clone())
+        .collect::<Vec<String>>();
+    ids.sort_by_key(|id| id.parse::<u32>().unwrap_or(0));
+
+    ids
+}
+
+#[test]
+fn account_includes_subaccounts() {
+    let company = load();
+    assert_eq!(ids(&company, "account:Expenses:Food"), ["6", "7", "8"]);
+    assert_eq!(ids(&company, "account:Expenses").len(), 10);
+}
+
+#[test]
+fn account_not_equal_excludes_the_account() {
+    let company = load();
+    assert_eq!(ids(&company, "account!=Expenses"), ["2"]);
+    assert_eq!(ids(&company, "account!=Food").len(), 8);
+}
+
+#[test]
+fn account_contains_matches_part_of_a_path() {
+    let company = load();
+    assert_eq!(ids(&company, "account~insur"), ["3", "4"]);
+}
+
+#[test]
+fn terms_are_joined() {
+    let company = load();
+    assert_eq!(ids(&company, "account:Food and amount>50"), ["7", "8"]);
+    assert_eq!(ids(&company, "memo~car and amount<=200"), ["4"]);
+    assert_eq!(ids(&company, "memo:\"taco bell\""), ["6"]);
+}
+
+#[test]
+fn dates_cover_a_whole_month_or_year() {
+    let company = load();
+    assert_eq!(ids(&company, "date:2014-11").len(), 11);
+    assert_eq!(ids(&company, "date<2014").len(), 0);
+    assert_eq!(ids(&company, "date!=2014").len(), 0);
+}
+
+#[test]
+fn tags_and_metadata_apply_the_comparison() {
+    let mut company = load();
+    for transaction in company.transactions.iter_mut() {
+        if transaction.id == "6" {
+            transaction.add_tag("lunch");
+            transaction.set_metadata("receipt", "r-12");
+        }
+    }
+
+    assert_eq!(ids(&company, "#lunch"), ["6"]);
+    assert_eq!(ids(&company, "tag!=lunch").len(), 10);
+    assert_eq!(ids(&company, "tag~lun"), ["6"]);
+    assert_eq!(ids(&company, "meta:receipt"), ["6"]);
+    assert_eq!(ids(&company, "meta:receipt=R-12"), ["6"]);
+    assert_eq!(ids(&company, "meta!=receipt").len(), 10);
+    assert_eq!(ids(&company, "meta~receipt=r-"), ["6"]);
+}
+
+#[test]
+fn sort_and_limit() {
+    let company = load();
+    let query = Query::parse(&company, "account:Expenses sort:-amount limit:2").unwrap();
+    let ids = query.run(&company).iter().map(|transaction| &transaction.id[..]).collect::<Vec<&str>>();
+    assert_eq!(ids, ["1", "5"]);
+}
+
+#[test]
+fn unsupported_comparisons_are_rejected() {
+    let company = load();
+    for input in ["amount~50", "date~2014", "account>Food", "tag<lunch", "meta>=receipt", "sort>amount", "limit!=3"] {
+        assert!(Query::parse(&company, input).is_err(), "{} should not parse", input);
+    }
+}
+
+#[test]
+fn bad_terms_are_rejected() {
+    let company = load();
+    for input in ["account:Nowhere", "amount>lots", "date:2014-13", "sort:color", "color:red", "memo", "memo:\"open"] {
+        assert!(Query::parse(&company, input).is_err(), "{} should not parse", input);
+    }
+}
+
+#[test]
+fn quotes_keep_words_together() {
+    assert_eq!(tokenize("memo:\"taco bell\" #lunch").unwrap(), ["memo:taco bell", "#lunch"]);
+    assert_eq!(tokenize("6 memo \"\"").unwrap(), ["6", "memo", ""]);
+    assert!(tokenize("memo:\"open").is_err());
+
+    // the command line reads the same way but lets a quote run to the end
+    assert_eq!(split_words("6 memo \"taco bell"), ["6", "memo", "taco bell"]);
+    assert_eq!(split_words(" 6  memo \"\" "), tokenize(" 6  memo \"\" ").unwrap());
+    assert_eq!(ids(&load(), "\"\" account:Food"), ["6", "7", "8"]);
+}
+
+#[test]
+fn describe_writes_the_query_back() {
+    let company = load();
+    for input in [
+        "account!=Expenses:Food",
+        "account~insur",
+        "amount>=50",
+        "date<2014-11",
+        "memo~\"taco bell\"",
+        "payee!=Sam",
+        "#lunch",
+        "tag!=lunch",
+        "meta!=receipt=r-12",
+        "account=Expenses sort:-amount limit:2",
+    ] {
+        let query = Query::parse(&company, input).unwrap();
+        assert_eq!(query.describe(), input);
+        let again = Query::parse(&company, &query.describe()).unwrap();
+        assert_eq!(again.describe(), input);
+    }
+}
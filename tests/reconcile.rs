@@ -0,0 +1,56 @@
+use money::company::Company;
+use money::reconcile::Reconciliation;
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+#[test]
+fn statements_are_read_from_the_command_line() {
+    let company = load();
+    assert!(Reconciliation::parse(&company, "9 2014-11-30 75").is_ok());
+    assert!(Reconciliation::parse(&company, "9 2014-11-30").is_err());
+    assert!(Reconciliation::parse(&company, "9 30/11/2014 75").is_err());
+    assert!(Reconciliation::parse(&company, "42 2014-11-30 75").is_err());
+
+    // postings after the statement date wait for the next one
+    let reconciliation = Reconciliation::parse(&company, "9 2014-11-27 0").unwrap();
+    assert!(reconciliation.get_candidates(&company).is_empty());
+}
+
+#[test]
+fn only_a_balanced_statement_locks_its_postings() {
+    let mut company = load();
+    let mut reconciliation = Reconciliation::parse(&company, "9 2014-11-30 75").unwrap();
+    assert_eq!(reconciliation.get_candidates(&company).len(), 3);
+    assert_eq!(reconciliation.get_difference(&company), 75.0);
+    assert!(reconciliation.finish(&mut company).is_err());
+
+    reconciliation.toggle("8");
+    reconciliation.toggle("6");
+    assert!(reconciliation.is_balanced(&company));
+    assert_eq!(reconciliation.finish(&mut company).unwrap(), 2);
+    assert_eq!(company.get_transaction_by_id("8").unwrap().get_status("9"), "reconciled");
+    assert_eq!(company.get_transaction_by_id("7").unwrap().get_status("9"), "uncleared");
+
+    // the next statement opens from what was agreed
+    let reconciliation = Reconciliation::parse(&company, "9 2014-11-30 161").unwrap();
+    assert_eq!(reconciliation.get_opening_balance(&company), 75.0);
+    assert_eq!(reconciliation.get_candidates(&company).len(), 1);
+}
+
+#[test]
+fn postings_the_bank_cleared_start_ticked() {
+    let mut company = load();
+    let mut transaction = company.get_transaction_by_id("7").cloned().unwrap();
+    transaction.set_status("9", "cleared");
+    company.update_transaction(transaction, true).unwrap();
+
+    let mut reconciliation = Reconciliation::parse(&company, "9 2014-11-30 86").unwrap();
+    assert!(reconciliation.is_balanced(&company));
+
+    // ticking twice leaves it as it was
+    reconciliation.toggle("6");
+    reconciliation.toggle("6");
+    assert!(reconciliation.is_balanced(&company));
+}
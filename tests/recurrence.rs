@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use money::company::Company;
+use money::recurrence::Recurrence;
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+fn monthly(start: &str) -> Recurrence {
+    let mut recurrence = Recurrence::new();
+    recurrence.set_frequency("monthly").set_start(date(start)).set_template("8", "1", "1200", "rent");
+    recurrence
+}
+
+fn occurrences(recurrence: &Recurrence, count: usize) -> Vec<NaiveDate> {
+    (0..count).filter_map(|n| recurrence.get_occurrence(n)).collect()
+}
+
+#[test]
+fn monthly_dates_clamp_to_short_months_and_come_back() {
+    // each date counts from the start, so February does not pull March short
+    let dates = ["2024-01-31", "2024-02-29", "2024-03-31", "2024-04-30", "2024-05-31"];
+    assert_eq!(occurrences(&monthly("2024-01-31"), 5), dates.map(date));
+
+    assert_eq!(monthly("2023-01-31").get_occurrence(1), Some(date("2023-02-28")));
+    assert_eq!(monthly("2023-12-31").get_occurrence(2), Some(date("2024-02-29")));
+    assert_eq!(monthly("2023-08-30").get_occurrence(6), Some(date("2024-02-29")));
+}
+
+#[test]
+fn yearly_dates_from_a_leap_day() {
+    let mut recurrence = monthly("2024-02-29");
+    recurrence.set_frequency("yearly");
+    let dates = ["2024-02-29", "2025-02-28", "2026-02-28", "2027-02-28", "2028-02-29"];
+    assert_eq!(occurrences(&recurrence, 5), dates.map(date));
+}
+
+#[test]
+fn the_last_business_day_skips_the_weekend() {
+    let mut recurrence = monthly("2025-08-01");
+    recurrence.set_last_business_day(true);
+    let dates = ["2025-08-29", "2025-09-30", "2025-10-31", "2025-11-28"];
+    assert_eq!(occurrences(&recurrence, 4), dates.map(date));
+}
+
+#[test]
+fn nothing_comes_after_the_end_date() {
+    let mut recurrence = monthly("2024-01-31");
+    recurrence.set_end(Some(date("2024-03-30")));
+    assert_eq!(occurrences(&recurrence, 5), ["2024-01-31", "2024-02-29"].map(date));
+    assert_eq!(recurrence.get_dates_until(date("2030-01-01")).len(), 2);
+}
+
+#[test]
+fn due_occurrences_are_posted_once() {
+    let mut company = Company::default().load("example.json").expect("example.json loads");
+    let mut recurrence = monthly("2024-01-31");
+    recurrence.set_id_in_company(&mut company);
+    company.insert_recurrence(recurrence);
+
+    let posted = company.post_due_recurrences(date("2024-04-29")).unwrap();
+    assert_eq!(posted.len(), 3);
+    let last = company.get_transaction_by_id(posted.last().unwrap()).unwrap();
+    assert_eq!(last.date, date("2024-03-31"));
+    assert!(company.post_due_recurrences(date("2024-04-29")).unwrap().is_empty());
+    assert_eq!(company.post_due_recurrences(date("2024-04-30")).unwrap().len(), 1);
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use money::company::Company;
+use money::sandbox::import_transactions;
+
+fn load() -> Company {
+    Company::default().load("example.json").expect("example.json loads")
+}
+
+fn write(company: &Company, name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("money-{}-{}.json", name, std::process::id()));
+    company.write_to(path.to_str().unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn importing_a_ledger_into_itself_posts_nothing() {
+    let mut company = load();
+    let path = write(&company, "itself");
+    assert_eq!(import_transactions(&mut company, &path).unwrap(), (0, 11, 0));
+    assert_eq!(company.transactions.len(), 11);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn transactions_are_matched_to_accounts_by_path() {
+    let mut other = load();
+    other.transactions.retain(|transaction| ["3", "5", "6", "7"].contains(&&transaction.id[..]));
+    for transaction in other.transactions.iter_mut().filter(|transaction| transaction.id == "7") {
+        transaction.set_memo("lunch");
+    }
+    // Expenses:Lodging is not an account here, even though Rent has its id,
+    // and Insurance is closed
+    other.rename_account("8", "Lodging").unwrap();
+    let path = write(&other, "paths");
+
+    let mut company = load();
+    company.close_account("7").unwrap();
+    assert_eq!(import_transactions(&mut company, &path).unwrap(), (1, 1, 2));
+
+    let posted = company.get_transaction_by_id("12").unwrap();
+    assert_eq!((&posted.debit[..], &posted.memo[..]), ("9", "lunch"));
+    assert_eq!(posted.get_status("9"), "uncleared");
+    assert!(company.get_acccount_by_id("9").unwrap().transactions.contains(&"12".to_string()));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn a_transaction_listed_twice_is_posted_once() {
+    let mut other = load();
+    let mut transaction = other.get_transaction_by_id("6").cloned().unwrap();
+    transaction.set_memo("late lunch");
+    other.post_transaction(transaction.clone());
+    other.post_transaction(transaction);
+    let path = write(&other, "twice");
+
+    let mut company = load();
+    assert_eq!(import_transactions(&mut company, &path).unwrap(), (1, 12, 0));
+    assert_eq!(company.transactions.len(), 12);
+    std::fs::remove_file(path).ok();
+}